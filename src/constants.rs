@@ -1,11 +1,22 @@
 // These values are all harvested from the OAuth 1.0 Spec.
 pub const ACCESS_TOKEN_NAME: &str = "access_token";
+pub const OAUTH_ACCEPTABLE_TIMESTAMPS_PARAM_NAME: &str = "oauth_acceptable_timestamps";
+pub const OAUTH_ACCEPTABLE_VERSIONS_PARAM_NAME: &str = "oauth_acceptable_versions";
+pub const OAUTH_BODY_HASH_PARAM_NAME: &str = "oauth_body_hash";
 pub const OAUTH_CALLBACK_PARAM_NAME: &str = "oauth_callback";
 pub const OAUTH_CALLBACK_OOB_VALUE: &str = "oob";
 pub const OAUTH_CONSUMER_KEY_PARAM_NAME: &str = "oauth_consumer_key";
+pub const OAUTH_PARAMETERS_ABSENT_PARAM_NAME: &str = "oauth_parameters_absent";
+pub const OAUTH_PARAMETERS_REJECTED_PARAM_NAME: &str = "oauth_parameters_rejected";
+pub const OAUTH_PROBLEM_PARAM_NAME: &str = "oauth_problem";
+pub const OAUTH_PROBLEM_ADVICE_PARAM_NAME: &str = "oauth_problem_advice";
 pub const OAUTH_SIGNATURE_PARAM_NAME: &str = "oauth_signature";
 pub const OAUTH_SIGNATURE_METHOD_PARAM_NAME: &str = "oauth_signature_method";
 pub const OAUTH_SIGNATURE_METHOD_HMAC_VALUE: &str = "HMAC-SHA1";
+pub const OAUTH_SIGNATURE_METHOD_HMAC_SHA256_VALUE: &str = "HMAC-SHA256";
+pub const OAUTH_SIGNATURE_METHOD_HMAC_SHA512_VALUE: &str = "HMAC-SHA512";
+pub const OAUTH_SIGNATURE_METHOD_RSA_VALUE: &str = "RSA-SHA1";
+pub const OAUTH_SIGNATURE_METHOD_PLAINTEXT_VALUE: &str = "PLAINTEXT";
 pub const OAUTH_TIMESTAMP_PARAM_NAME: &str = "oauth_timestamp";
 pub const OAUTH_TOKEN_PARAM_NAME: &str = "oauth_token";
 pub const OAUTH_TOKEN_SECRET_PARAM_NAME: &str = "oauth_token_secret";
@@ -13,5 +24,7 @@ pub const OAUTH_NONCE_PARAM_NAME: &str = "oauth_nonce";
 pub const OAUTH_VERIFIER_PARAM_NAME: &str = "oauth_verifier";
 pub const OAUTH_VERSION_PARAM_NAME: &str = "oauth_version";
 pub const OAUTH_VERSION_VALUE: &str = "1.0";
+pub const PROFILE_TABLE_NAME: &str = "profile";
+pub const TOKEN_LAST_USE: &str = "token_last_use";
 pub const TOKEN_SAVE_TIME: &str = "token_save_time";
 pub const TOKEN_SECRET_NAME: &str = "token_secret";
@@ -0,0 +1,144 @@
+//! Ways of getting the user to authorize the request token.
+
+use std::fmt::Debug;
+use std::io::Write;
+use std::time::Duration;
+
+use log::warn;
+use url::Url;
+
+use crate::consumer::CallbackServer;
+use crate::error::{OagainError, Result};
+
+/// Gets the user to authorize a request token, and returns the verification code.
+///
+/// The built-in authorizers are [TerminalPrompt], [BrowserPrompt], [LoopbackCallback], and
+/// [Headless]. Others (e.g., a GUI dialog) can be supplied by implementing this trait and passing
+/// them to `Builder::set_user_authorizer()`.
+pub trait UserAuthorizer: Debug + Send {
+    /// Called before the request token is retrieved. Returns the callback URL to send with it,
+    /// if this authorizer receives the provider's redirect.
+    fn prepare(&mut self) -> Result<Option<Url>> {
+        Ok(None)
+    }
+
+    /// Has the user authorize `request_token` at `url`, and returns the verification code.
+    fn authorize(&mut self, url: &Url, request_token: &str) -> Result<String>;
+}
+
+/// Prints the authorization URL, and reads the verification code from stdin.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TerminalPrompt;
+
+impl UserAuthorizer for TerminalPrompt {
+    fn authorize(&mut self, url: &Url, _: &str) -> Result<String> {
+        println!(
+            "Go to the following URL and follow the instructions:\n\n    {}\n\n",
+            url
+        );
+        read_code()
+    }
+}
+
+/// Opens the authorization URL in the browser, and reads the verification code from stdin.
+/// This is the default.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BrowserPrompt;
+
+impl UserAuthorizer for BrowserPrompt {
+    fn authorize(&mut self, url: &Url, _: &str) -> Result<String> {
+        println!(
+            "Opening the following URL in your browser. If it doesn't open, go there yourself \
+            and follow the instructions:\n\n    {}\n\n",
+            url
+        );
+        open_browser(url);
+        read_code()
+    }
+}
+
+/// Listens on 127.0.0.1 for the provider's redirect, which carries the verification code, using
+/// a [CallbackServer]. The authorization URL is opened in the browser, and printed.
+#[derive(Debug)]
+pub struct LoopbackCallback {
+    port: u16,
+    timeout: Duration,
+    open_browser: bool,
+    server: Option<CallbackServer>,
+}
+
+impl LoopbackCallback {
+    /// Listens on `port`, or on any free port if it's 0. The user has 5 minutes to authorize.
+    pub fn new(port: u16) -> LoopbackCallback {
+        LoopbackCallback {
+            port,
+            timeout: Duration::from_secs(5 * 60),
+            open_browser: true,
+            server: None,
+        }
+    }
+
+    pub fn set_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Whether to open the authorization URL in the browser, as well as printing it.
+    pub fn open_browser(mut self, open_browser: bool) -> Self {
+        self.open_browser = open_browser;
+        self
+    }
+}
+
+impl UserAuthorizer for LoopbackCallback {
+    fn prepare(&mut self) -> Result<Option<Url>> {
+        // A server left by an attempt that failed before authorize() still holds a fixed port,
+        // and may have a stale redirect waiting, so it's closed first.
+        self.server = None;
+        let server = CallbackServer::bind(self.port)?;
+        let url = server.url().clone();
+        self.server = Some(server);
+        Ok(Some(url))
+    }
+
+    fn authorize(&mut self, url: &Url, request_token: &str) -> Result<String> {
+        // The server is only needed for one authorization.
+        let server = self.server.take().ok_or_else(|| {
+            OagainError::CallbackError("the callback server wasn't started".to_string())
+        })?;
+        println!(
+            "Go to the following URL and follow the instructions:\n\n    {}\n\n",
+            url
+        );
+        if self.open_browser {
+            open_browser(url);
+        }
+        server.wait_for_verifier(request_token, self.timeout)
+    }
+}
+
+/// Fails with `OagainError::NeedsUserAuth`, which carries the authorization URL, for services
+/// that can't talk to the user. The consumer keeps the request token, so once the user has
+/// authorized elsewhere, finish with `set_verification_code()` and `retrieve_access_token()`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Headless;
+
+impl UserAuthorizer for Headless {
+    fn authorize(&mut self, url: &Url, _: &str) -> Result<String> {
+        Err(OagainError::NeedsUserAuth(url.clone()))
+    }
+}
+
+fn open_browser(url: &Url) {
+    if let Err(err) = open::that(url.to_string()) {
+        warn!("Failed to open the browser: {}", err);
+    }
+}
+
+fn read_code() -> Result<String> {
+    print!("Input the verification code received from the server: ");
+    std::io::stdout().flush()?;
+    let mut code = String::new();
+    std::io::stdin().read_line(&mut code)?;
+    Ok(code.trim().to_string())
+}
@@ -0,0 +1,284 @@
+//! Loads a [Builder] from a TOML config file.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{Duration, NaiveTime};
+use chrono_tz::Tz;
+use serde::Deserialize;
+use url::Url;
+
+use crate::constants::{
+    OAUTH_SIGNATURE_METHOD_HMAC_SHA256_VALUE, OAUTH_SIGNATURE_METHOD_HMAC_SHA512_VALUE,
+    OAUTH_SIGNATURE_METHOD_HMAC_VALUE, OAUTH_SIGNATURE_METHOD_PLAINTEXT_VALUE,
+    OAUTH_SIGNATURE_METHOD_RSA_VALUE,
+};
+use crate::consumer::builder::{Builder, UrlSetter};
+use crate::error::{OagainError, Result};
+use crate::signing::HmacSha1;
+use crate::token_lifetime::{
+    DailyCutoff, FixedAge, IdleTimeout, NeverExpires, TokenLifetimePolicy,
+};
+use crate::token_store::{JsonFileStore, MemoryStore, Passphrase};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Config {
+    request_token_url: Option<String>,
+    user_authorization_url: Option<String>,
+    access_token_url: Option<String>,
+    renew_access_token_url: Option<String>,
+    revoke_access_token_url: Option<String>,
+    callback_url: Option<String>,
+    callback_server_port: Option<u16>,
+    user_auth_key_param_name: Option<String>,
+    user_auth_token_param_name: Option<String>,
+    profile: Option<String>,
+    secrets_file: Option<PathBuf>,
+    signature: Option<SignatureConfig>,
+    token_lifetime: Option<TokenLifetimeConfig>,
+    store: Option<StoreConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SignatureConfig {
+    method: String,
+    rsa_private_key_file: Option<PathBuf>,
+    body_hash: Option<bool>,
+    allow_insecure_plaintext: Option<bool>,
+}
+
+// Either `never_expires = true` or at least one limit, so that a typo or an emptied table doesn't
+// quietly turn expiry off.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TokenLifetimeConfig {
+    never_expires: Option<bool>,
+    idle_timeout_minutes: Option<i64>,
+    max_age_minutes: Option<i64>,
+    daily_cutoff: Option<NaiveTime>,
+    timezone: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase", deny_unknown_fields)]
+enum StoreConfig {
+    Toml {
+        path: PathBuf,
+    },
+    Json {
+        path: PathBuf,
+    },
+    Encrypted {
+        path: PathBuf,
+        passphrase_env: String,
+    },
+    Memory,
+}
+
+impl TokenLifetimeConfig {
+    fn policy(&self) -> Result<Box<dyn TokenLifetimePolicy>> {
+        let minutes = |name: &str, minutes: Option<i64>| match minutes {
+            Some(minutes) if minutes < 0 => Err(OagainError::BadConfig(format!(
+                "{} can't be negative",
+                name
+            ))),
+            minutes => Ok(minutes.map(Duration::minutes)),
+        };
+
+        let mut limits: Vec<Box<dyn TokenLifetimePolicy>> = Vec::new();
+        if let Some(timeout) = minutes("idle_timeout_minutes", self.idle_timeout_minutes)? {
+            limits.push(Box::new(IdleTimeout(timeout)));
+        }
+        if let Some(age) = minutes("max_age_minutes", self.max_age_minutes)? {
+            limits.push(Box::new(FixedAge(age)));
+        }
+        match (self.daily_cutoff, &self.timezone) {
+            (Some(time), Some(timezone)) => {
+                let timezone = timezone.parse::<Tz>().map_err(|_| {
+                    OagainError::BadConfig(format!("{} is not a timezone", timezone))
+                })?;
+                limits.push(Box::new(DailyCutoff::new(timezone, time)));
+            }
+            (Some(_), None) => {
+                return Err(OagainError::BadConfig(
+                    "daily_cutoff needs a timezone".to_string(),
+                ))
+            }
+            (None, Some(_)) => {
+                return Err(OagainError::BadConfig(
+                    "timezone needs a daily_cutoff".to_string(),
+                ))
+            }
+            (None, None) => {}
+        }
+
+        match (self.never_expires.unwrap_or(false), limits.is_empty()) {
+            (true, true) => Ok(Box::new(NeverExpires)),
+            (true, false) => Err(OagainError::BadConfig(
+                "never_expires can't have limits".to_string(),
+            )),
+            (false, true) => Err(OagainError::BadConfig(
+                "token_lifetime needs a limit, or never_expires = true".to_string(),
+            )),
+            // A token expires when any limit says so. unwrap: there is at least one.
+            (false, false) => Ok(limits
+                .into_iter()
+                .reduce(|policy, limit| Box::new((policy, limit)))
+                .unwrap()),
+        }
+    }
+}
+
+impl Builder {
+    /// A builder set up from the config file at `path`, so that a new provider can be targeted
+    /// without recompiling. For example:
+    ///
+    /// ```toml
+    /// request_token_url = "https://api.example.com/oauth/request_token"
+    /// user_authorization_url = "https://example.com/oauth/authorize"
+    /// access_token_url = "https://api.example.com/oauth/access_token"
+    /// user_auth_key_param_name = "key"
+    /// user_auth_token_param_name = "token"
+    /// callback_url = "http://127.0.0.1:8080/callback"
+    /// secrets_file = "secrets.toml"
+    ///
+    /// [signature]
+    /// method = "HMAC-SHA256"
+    ///
+    /// [token_lifetime]
+    /// idle_timeout_minutes = 120
+    /// daily_cutoff = "00:00"
+    /// timezone = "US/Eastern"
+    ///
+    /// [store]
+    /// kind = "toml"
+    /// path = "save.toml"
+    /// ```
+    ///
+    /// Every setting is optional. There are also `renew_access_token_url`,
+    /// `revoke_access_token_url`, `callback_server_port`, and `profile`; `rsa_private_key_file`,
+    /// `body_hash`, and `allow_insecure_plaintext` under `[signature]`; and `max_age_minutes` under
+    /// `[token_lifetime]`, which needs at least one limit, or `never_expires = true`. The store
+    /// `kind` is one of `toml`, `json`, `encrypted` (which also needs `passphrase_env`), or
+    /// `memory`. Relative paths are relative to the config file.
+    pub fn from_config_file(path: impl AsRef<Path>) -> Result<Builder> {
+        Builder::default().use_config_file(path)
+    }
+
+    /// Apply the settings in the config file at `path`. Like every other setter, this replaces
+    /// values set by earlier calls, and is replaced by later ones.
+    pub fn use_config_file(self, path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let config = toml::from_str::<Config>(&fs::read_to_string(path)?)?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+
+        let mut builder = self;
+        if let Some(profile) = config.profile {
+            builder = builder.use_profile(profile);
+        }
+        let urls: [(&str, Option<String>, UrlSetter); 6] = [
+            (
+                "request_token_url",
+                config.request_token_url,
+                Builder::set_request_token_url,
+            ),
+            (
+                "user_authorization_url",
+                config.user_authorization_url,
+                Builder::set_user_authorization_url,
+            ),
+            (
+                "access_token_url",
+                config.access_token_url,
+                Builder::set_access_token_url,
+            ),
+            (
+                "renew_access_token_url",
+                config.renew_access_token_url,
+                Builder::set_renew_access_token_url,
+            ),
+            (
+                "revoke_access_token_url",
+                config.revoke_access_token_url,
+                Builder::set_revoke_access_token_url,
+            ),
+            (
+                "callback_url",
+                config.callback_url,
+                Builder::set_callback_url,
+            ),
+        ];
+        for (name, url, set_url) in urls {
+            if let Some(url) = url {
+                let url = Url::parse(&url)
+                    .map_err(|_| OagainError::BadConfig(format!("{} is not a URL", name)))?;
+                builder = set_url(builder, url)?;
+            }
+        }
+        if let Some(port) = config.callback_server_port {
+            builder = builder.use_callback_server(port);
+        }
+        if let Some(name) = config.user_auth_key_param_name {
+            builder = builder.set_user_auth_key_param_name(name);
+        }
+        if let Some(name) = config.user_auth_token_param_name {
+            builder = builder.set_user_auth_token_param_name(name);
+        }
+
+        if let Some(signature) = config.signature {
+            builder = match signature.method.as_str() {
+                OAUTH_SIGNATURE_METHOD_HMAC_VALUE => builder.set_signature_method(HmacSha1),
+                OAUTH_SIGNATURE_METHOD_HMAC_SHA256_VALUE => builder.use_hmac_sha256(),
+                OAUTH_SIGNATURE_METHOD_HMAC_SHA512_VALUE => builder.use_hmac_sha512(),
+                OAUTH_SIGNATURE_METHOD_PLAINTEXT_VALUE => builder.use_plaintext(),
+                OAUTH_SIGNATURE_METHOD_RSA_VALUE => {
+                    let Some(key_file) = &signature.rsa_private_key_file else {
+                        return Err(OagainError::BadConfig(
+                            "RSA-SHA1 needs an rsa_private_key_file".to_string(),
+                        ));
+                    };
+                    builder.use_rsa_private_key_file(dir.join(key_file))?
+                }
+                method => {
+                    return Err(OagainError::BadConfig(format!(
+                        "unknown signature method {}",
+                        method
+                    )))
+                }
+            };
+            if let Some(body_hash) = signature.body_hash {
+                builder = builder.use_body_hash(body_hash);
+            }
+            if let Some(allow) = signature.allow_insecure_plaintext {
+                builder = builder.allow_insecure_plaintext(allow);
+            }
+        }
+
+        if let Some(token_lifetime) = config.token_lifetime {
+            builder = builder.set_token_lifetime_policy(token_lifetime.policy()?);
+        }
+        if let Some(secrets_file) = config.secrets_file {
+            builder = builder.use_secrets_file(dir.join(secrets_file))?;
+        }
+        builder = match config.store {
+            Some(StoreConfig::Toml { path }) => builder.use_save_file(dir.join(path))?,
+            Some(StoreConfig::Json { .. }) if builder.profile.is_some() => {
+                return Err(OagainError::ProfileUnsupported("JSON token store"))
+            }
+            Some(StoreConfig::Json { path }) => {
+                builder.use_token_store(JsonFileStore::new(dir.join(path))?)
+            }
+            Some(StoreConfig::Encrypted {
+                path,
+                passphrase_env,
+            }) => {
+                builder.use_encrypted_save_file(dir.join(path), Passphrase::env(passphrase_env))?
+            }
+            Some(StoreConfig::Memory) => builder.use_token_store(MemoryStore::new()),
+            None => builder,
+        };
+        Ok(builder)
+    }
+}
@@ -5,34 +5,50 @@
 // - request token url
 // - user auth url
 // - access token url
+// - renew access token url
+// - revoke access token url
+// - callback url
 // - user auth key param name
 // - user auth token param name
 //
 // - consumer key
 // - consumer secret
+// - signature method
+// - token lifetime policy
 //
 // - preset
+// - profile
+// - environment variables
 
-use chrono::{DateTime, Duration, Utc};
+use chrono::{Duration, Utc};
 use log::{info, warn};
-use std::fs::{File, Permissions};
+use std::env::VarError;
+use std::fs::File;
 use std::io::Read;
-use std::os::unix::fs::{MetadataExt, PermissionsExt};
-use std::path::{Path, PathBuf};
+use std::mem;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::sync::Mutex;
 
 use toml::Value;
 use url::Url;
 
-use crate::constants::{ACCESS_TOKEN_NAME, TOKEN_SAVE_TIME, TOKEN_SECRET_NAME};
 use crate::consumer::builder::preset::Preset;
 use crate::consumer::state::ConsumerState;
 use crate::consumer::state::ConsumerState::FullAuth;
-use crate::consumer::Consumer;
+use crate::consumer::{
+    BrowserPrompt, Consumer, ConsumerConfig, LoopbackCallback, Session, UserAuthorizer,
+};
 use crate::error::OagainError::BadUrl;
 use crate::error::{OagainError, Result};
-use crate::nonce_provider::{BasicNonce, NonceProvider};
+use crate::nonce_provider::{BasicNonce, NonceProvider, SystemEpochProvider};
+use crate::profile::{profile_label, profile_table};
+use crate::signing::{HmacSha1, HmacSha256, HmacSha512, PlainText, RsaSha1, SignatureMethod};
+use crate::token_lifetime::{FixedAge, TokenLifetimePolicy, TokenStatus, TokenTimes};
+use crate::token_store::{EncryptedFileStore, Passphrase, TokenStore, TomlFileStore};
 use crate::BasicConsumer;
 
+pub mod config;
 pub mod preset;
 
 #[derive(Debug)]
@@ -40,18 +56,39 @@ pub struct Builder {
     request_token_url: Option<Url>,
     user_authorization_url: Option<Url>,
     access_token_url: Option<Url>,
+    renew_access_token_url: Option<Url>,
+    revoke_access_token_url: Option<Url>,
+    callback_url: Option<Url>,
+    user_authorizer: Box<dyn UserAuthorizer>,
 
     user_auth_key_param_name: String,
     user_auth_token_param_name: String,
 
     consumer_key: Option<String>,
     consumer_secret: Option<String>,
+    signature_method: Box<dyn SignatureMethod>,
+    allow_insecure_plaintext: bool,
+    use_body_hash: bool,
 
-    save_file: Option<PathBuf>,
+    token_lifetime: Box<dyn TokenLifetimePolicy>,
+
+    profile: Option<String>,
+    secrets: Option<toml::Table>,
+    token_store: Option<StoreSetting>,
+    use_saved_token: bool,
     init_state: ConsumerState,
+    token_times: Option<TokenTimes>,
 }
 
-fn read_key_and_secret(path: impl AsRef<Path>) -> Result<(String, String)> {
+// The save files are opened when they're set, but their profile is only known at build().
+#[derive(Debug)]
+enum StoreSetting {
+    SaveFile(TomlFileStore),
+    EncryptedSaveFile(EncryptedFileStore),
+    Other(Box<dyn TokenStore>),
+}
+
+fn read_secrets_file(path: impl AsRef<Path>) -> Result<toml::Table> {
     let mut s = String::new();
     let mut f = File::open(&path)?;
     if f.metadata()?.mode() & 0o777 != 0o600 {
@@ -61,7 +98,12 @@ fn read_key_and_secret(path: impl AsRef<Path>) -> Result<(String, String)> {
         );
     }
     f.read_to_string(&mut s)?;
-    let table = s.parse::<toml::Table>()?;
+    Ok(s.parse::<toml::Table>()?)
+}
+
+fn key_and_secret(table: &toml::Table, profile: Option<&str>) -> Result<(String, String)> {
+    let table = profile_table(table, profile)
+        .ok_or_else(|| OagainError::MissingProfile(profile.unwrap_or_default().to_string()))?;
 
     let Some(Value::String(key)) = table.get("token") else {
         return Err(OagainError::MissingConsumerToken("in secrets file"));
@@ -72,34 +114,19 @@ fn read_key_and_secret(path: impl AsRef<Path>) -> Result<(String, String)> {
     Ok((key.to_string(), secret.to_string()))
 }
 
-fn read_access_key_and_secret(path: impl AsRef<Path>) -> Result<(String, String)> {
-    let mut s = String::new();
-    let mut f = File::open(&path)?;
-    if f.metadata()?.mode() & 0o777 != 0o600 {
-        warn!(
-            "File permissions on save file should be 0600: {}",
-            path.as_ref().to_string_lossy()
-        );
-    }
-    f.read_to_string(&mut s)?;
-    let table = s.parse::<toml::Table>()?;
+type UrlSetter = fn(Builder, Url) -> Result<Builder>;
 
-    let Some(Value::String(key)) = table.get(ACCESS_TOKEN_NAME) else {
-        return Err(OagainError::MissingAccessToken);
-    };
-    let Some(Value::String(secret)) = table.get(TOKEN_SECRET_NAME) else {
-        return Err(OagainError::MissingTokenSecret);
-    };
-    let Some(Value::Datetime(save_time)) = table.get(TOKEN_SAVE_TIME) else {
-        return Err(OagainError::MissingTokenSaveTime);
-    };
-    let chrono_date_time = DateTime::parse_from_rfc3339(&save_time.to_string())?;
-    let age = Utc::now().signed_duration_since(&chrono_date_time);
-    if age > Duration::hours(2) {
-        return Err(OagainError::OldToken);
-    }
+// Looks up an environment variable, like `std::env::var()`.
+type EnvLookup<'a> = &'a dyn Fn(&str) -> std::result::Result<String, VarError>;
 
-    Ok((key.to_string(), secret.to_string()))
+// Returns None if the variable is unset or empty.
+fn env_var(lookup: EnvLookup, name: &str) -> Result<Option<String>> {
+    match lookup(name) {
+        Ok(value) if value.is_empty() => Ok(None),
+        Ok(value) => Ok(Some(value)),
+        Err(VarError::NotPresent) => Ok(None),
+        Err(VarError::NotUnicode(_)) => Err(OagainError::BadEnvVar(name.to_string())),
+    }
 }
 
 impl Default for Builder {
@@ -108,39 +135,67 @@ impl Default for Builder {
             request_token_url: None,
             user_authorization_url: None,
             access_token_url: None,
+            renew_access_token_url: None,
+            revoke_access_token_url: None,
+            callback_url: None,
+            user_authorizer: Box::new(BrowserPrompt),
             user_auth_key_param_name: "oauth_consumer_key".to_string(),
             user_auth_token_param_name: "oauth_token".to_string(),
             consumer_key: None,
             consumer_secret: None,
-            save_file: None,
+            signature_method: Box::new(HmacSha1),
+            allow_insecure_plaintext: false,
+            use_body_hash: true,
+            // The original hard-coded behavior. ETradePreset sets eTrade's real limits.
+            token_lifetime: Box::new(FixedAge(Duration::hours(2))),
+            profile: None,
+            secrets: None,
+            token_store: None,
+            use_saved_token: false,
             init_state: Default::default(),
+            token_times: None,
         }
     }
 }
 
 impl Builder {
-    pub fn build_with_nonce_provider<NP: NonceProvider>(self, np: NP) -> Result<Consumer<NP>> {
+    pub fn build_with_nonce_provider<NP: NonceProvider>(mut self, np: NP) -> Result<Consumer<NP>> {
+        self.apply_secrets()?;
+        let token_store = self.open_token_store()?;
+        let status = self.token_times.map(|times| {
+            self.token_lifetime
+                .status(times.issued, times.last_used, Utc::now())
+        });
+        let (state, token_times) = match status {
+            Some(TokenStatus::Expired) => {
+                info!(
+                    "The saved access token has expired{}.",
+                    profile_label(&self.profile)
+                );
+                (ConsumerState::NoAuth, None)
+            }
+            // A token that needs renewal is useless if there's nowhere to renew it.
+            Some(TokenStatus::NeedsRenewal) if self.renew_access_token_url.is_none() => {
+                info!(
+                    "The saved access token{} is inactive, and there is no renew URL.",
+                    profile_label(&self.profile)
+                );
+                (ConsumerState::NoAuth, None)
+            }
+            _ => (mem::take(&mut self.init_state), self.token_times),
+        };
+        let user_authorizer = Mutex::new(mem::replace(
+            &mut self.user_authorizer,
+            Box::new(BrowserPrompt),
+        ));
+        let profile = self.profile.clone();
         Ok(Consumer {
-            consumer_key: self
-                .consumer_key
-                .ok_or(OagainError::MissingConsumerToken("in builder"))?,
-            consumer_secret: self
-                .consumer_secret
-                .ok_or(OagainError::MissingConsumerSecret("in builder"))?,
-            nonce_provider: np,
-            request_token_url: self
-                .request_token_url
-                .ok_or(OagainError::MissingRequestTokenUrl)?,
-            user_authorization_url: self
-                .user_authorization_url
-                .ok_or(OagainError::MissingAuthUrl)?,
-            access_token_url: self
-                .access_token_url
-                .ok_or(OagainError::MissingAccessTokenUrl)?,
-            user_auth_key_param_name: self.user_auth_key_param_name,
-            user_auth_token_param_name: self.user_auth_token_param_name,
-            save_file: self.save_file,
-            state: self.init_state,
+            config: self.into_config(np)?,
+            user_authorizer,
+            profile,
+            token_store,
+            saved_last_use: Mutex::new(token_times.map(|times| times.last_used)),
+            session: Mutex::new(Session { state, token_times }),
         })
     }
 
@@ -148,6 +203,21 @@ impl Builder {
         self.build_with_nonce_provider(BasicNonce::default())
     }
 
+    /// Builds just the settings that don't depend on the user, for signing requests on behalf of
+    /// many users. Any token set on the builder is ignored.
+    pub fn build_config_with_nonce_provider<NP: NonceProvider>(
+        mut self,
+        np: NP,
+    ) -> Result<ConsumerConfig<NP>> {
+        // The token store is never opened, so a broken save file can't get in the way.
+        self.apply_secrets()?;
+        self.into_config(np)
+    }
+
+    pub fn build_config(self) -> Result<ConsumerConfig<BasicNonce<SystemEpochProvider>>> {
+        self.build_config_with_nonce_provider(BasicNonce::default())
+    }
+
     pub fn use_preset(self, preset: impl Preset) -> Result<Self> {
         preset.setup_builder(self)
     }
@@ -167,6 +237,39 @@ impl Builder {
         Ok(self)
     }
 
+    /// The URL used to renew an inactive access token. Without it, stale tokens in the save file
+    /// are discarded.
+    pub fn set_renew_access_token_url(mut self, url: impl TryInto<Url>) -> Result<Self> {
+        self.renew_access_token_url = Some(url.try_into().map_err(|_| BadUrl)?);
+        Ok(self)
+    }
+
+    /// The URL used to revoke the access token in `Consumer::revoke()`.
+    pub fn set_revoke_access_token_url(mut self, url: impl TryInto<Url>) -> Result<Self> {
+        self.revoke_access_token_url = Some(url.try_into().map_err(|_| BadUrl)?);
+        Ok(self)
+    }
+
+    /// Where the provider should send the user after authorization. Without one, the user is
+    /// shown the verification code instead (`oauth_callback=oob`).
+    pub fn set_callback_url(mut self, url: impl TryInto<Url>) -> Result<Self> {
+        self.callback_url = Some(url.try_into().map_err(|_| BadUrl)?);
+        Ok(self)
+    }
+
+    /// How the user is asked to authorize. The default is [BrowserPrompt].
+    pub fn set_user_authorizer(mut self, authorizer: impl UserAuthorizer + 'static) -> Self {
+        self.user_authorizer = Box::new(authorizer);
+        self
+    }
+
+    /// When authorizing, listen on 127.0.0.1:`port` (any free port if 0) for the provider's
+    /// redirect, instead of asking the user to paste the verification code. This replaces the
+    /// callback URL. Same as `set_user_authorizer(LoopbackCallback::new(port))`.
+    pub fn use_callback_server(self, port: u16) -> Self {
+        self.set_user_authorizer(LoopbackCallback::new(port))
+    }
+
     pub fn set_user_auth_key_param_name(mut self, val: impl Into<String>) -> Self {
         self.user_auth_key_param_name = val.into();
         self
@@ -187,31 +290,291 @@ impl Builder {
         self
     }
 
-    pub fn use_secrets_file(self, path: impl AsRef<Path>) -> Result<Self> {
-        let (consumer_key, consumer_secret) = read_key_and_secret(path)?;
-        Ok(self
-            .set_consumer_key(consumer_key)
-            .set_consumer_secret(consumer_secret))
+    /// Sign requests with `method`. The default is HMAC-SHA1.
+    pub fn set_signature_method(mut self, method: impl SignatureMethod + 'static) -> Self {
+        self.signature_method = Box::new(method);
+        self
+    }
+
+    /// Sign requests with HMAC-SHA256.
+    pub fn use_hmac_sha256(self) -> Self {
+        self.set_signature_method(HmacSha256)
+    }
+
+    /// Sign requests with HMAC-SHA512.
+    pub fn use_hmac_sha512(self) -> Self {
+        self.set_signature_method(HmacSha512)
+    }
+
+    /// Sign requests with PLAINTEXT. Requests to non-https URLs will be refused.
+    pub fn use_plaintext(self) -> Self {
+        self.set_signature_method(PlainText)
+    }
+
+    /// Allow PLAINTEXT signatures to be sent to non-https URLs, with a warning.
+    /// This is only useful for test sandboxes. Never do this in production.
+    pub fn allow_insecure_plaintext(mut self, allow: bool) -> Self {
+        self.allow_insecure_plaintext = allow;
+        self
+    }
+
+    /// Whether to send `oauth_body_hash` with bodies that aren't form-encoded. The default is
+    /// true. Turn it off for providers that reject requests containing it.
+    pub fn use_body_hash(mut self, use_body_hash: bool) -> Self {
+        self.use_body_hash = use_body_hash;
+        self
+    }
+
+    /// Decide when access tokens expire with `policy`. By default, tokens expire two hours after
+    /// they are issued.
+    ///
+    /// Policies that look at the last-use time (e.g., [IdleTimeout](crate::IdleTimeout)) should
+    /// allow for it being saved at most once a minute: another process reading the save file may
+    /// see a use up to a minute late, so a token can look idle up to a minute early.
+    pub fn set_token_lifetime_policy(mut self, policy: impl TokenLifetimePolicy + 'static) -> Self {
+        self.token_lifetime = Box::new(policy);
+        self
+    }
+
+    /// Sign requests with RSA-SHA1 using the PEM-encoded private key.
+    pub fn use_rsa_private_key(self, pem: impl AsRef<str>) -> Result<Self> {
+        Ok(self.set_signature_method(RsaSha1::from_pem(pem)?))
+    }
+
+    /// Sign requests with RSA-SHA1 using the PEM-encoded private key stored at `path`.
+    pub fn use_rsa_private_key_file(self, path: impl AsRef<Path>) -> Result<Self> {
+        let mut pem = String::new();
+        let mut f = File::open(&path)?;
+        if f.metadata()?.mode() & 0o777 != 0o600 {
+            warn!(
+                "File permissions on private key file should be 0600: {}",
+                path.as_ref().to_string_lossy()
+            );
+        }
+        f.read_to_string(&mut pem)?;
+        self.use_rsa_private_key(pem)
+    }
+
+    /// Use the `[profile.name]` tables in the secrets and save files, instead of the top level.
+    /// The tables are picked at `build()`, so this can be called before or after naming the files.
+    /// The encrypted save file can't hold profiles, so building with both fails.
+    pub fn use_profile(mut self, name: impl Into<String>) -> Self {
+        self.profile = Some(name.into());
+        self
+    }
+
+    // Records the profile in errors, since the same file can hold several.
+    fn in_profile(&self, err: OagainError) -> OagainError {
+        match &self.profile {
+            Some(profile) => OagainError::InProfile {
+                profile: profile.clone(),
+                source: Box::new(err),
+            },
+            None => err,
+        }
+    }
+
+    /// Read the consumer key and secret from a TOML file at `path`. They replace any key and
+    /// secret already set, and are replaced by ones set later.
+    pub fn use_secrets_file(mut self, path: impl AsRef<Path>) -> Result<Self> {
+        self.secrets = Some(read_secrets_file(path)?);
+        self.consumer_key = None;
+        self.consumer_secret = None;
+        Ok(self)
+    }
+
+    // Takes the key, secret, and saved token from the profile's tables, and makes the store.
+    fn into_config<NP: NonceProvider>(self, np: NP) -> Result<ConsumerConfig<NP>> {
+        Ok(ConsumerConfig {
+            consumer_key: self
+                .consumer_key
+                .ok_or(OagainError::MissingConsumerToken("in builder"))?,
+            consumer_secret: match self.consumer_secret {
+                Some(secret) => secret,
+                // e.g., RSA-SHA1 signs with the private key, so the consumer secret is never
+                // used.
+                None if !self.signature_method.uses_consumer_secret() => String::new(),
+                None => return Err(OagainError::MissingConsumerSecret("in builder")),
+            },
+            signature_method: self.signature_method,
+            allow_insecure_plaintext: self.allow_insecure_plaintext,
+            use_body_hash: self.use_body_hash,
+            nonce_provider: np,
+            request_token_url: self
+                .request_token_url
+                .ok_or(OagainError::MissingRequestTokenUrl)?,
+            user_authorization_url: self
+                .user_authorization_url
+                .ok_or(OagainError::MissingAuthUrl)?,
+            access_token_url: self
+                .access_token_url
+                .ok_or(OagainError::MissingAccessTokenUrl)?,
+            renew_access_token_url: self.renew_access_token_url,
+            revoke_access_token_url: self.revoke_access_token_url,
+            callback_url: self.callback_url,
+            user_auth_key_param_name: self.user_auth_key_param_name,
+            user_auth_token_param_name: self.user_auth_token_param_name,
+            token_lifetime: self.token_lifetime,
+        })
     }
 
-    pub fn use_save_file(mut self, path_in: impl AsRef<Path>) -> Result<Self> {
-        let path = path_in.as_ref();
-        self.save_file = Some(path.to_path_buf());
+    fn apply_secrets(&mut self) -> Result<()> {
+        if let Some(secrets) = self.secrets.take() {
+            if self.consumer_key.is_none() || self.consumer_secret.is_none() {
+                let (key, secret) = key_and_secret(&secrets, self.profile.as_deref())
+                    .map_err(|err| self.in_profile(err))?;
+                self.consumer_key.get_or_insert(key);
+                self.consumer_secret.get_or_insert(secret);
+            }
+        }
+        Ok(())
+    }
 
-        if path.try_exists()? {
-            if let Ok((access_token, token_secret)) = read_access_key_and_secret(path) {
-                self.init_state = FullAuth {
-                    access_token,
-                    token_secret,
+    fn open_token_store(&mut self) -> Result<Option<Box<dyn TokenStore>>> {
+        let store: Box<dyn TokenStore> = match (self.token_store.take(), &self.profile) {
+            (None, _) => return Ok(None),
+            (Some(StoreSetting::SaveFile(store)), Some(profile)) => {
+                Box::new(store.with_profile(profile))
+            }
+            (Some(StoreSetting::SaveFile(store)), None) => Box::new(store),
+            (Some(StoreSetting::EncryptedSaveFile(_)), Some(_)) => {
+                return Err(OagainError::ProfileUnsupported("encrypted save file"))
+            }
+            (Some(StoreSetting::EncryptedSaveFile(store)), None) => Box::new(store),
+            (Some(StoreSetting::Other(store)), _) => store,
+        };
+        if self.use_saved_token {
+            match store.load() {
+                Ok(Some(token)) => {
+                    self.init_state = FullAuth {
+                        access_token: token.access_token,
+                        token_secret: token.token_secret,
+                    };
+                    self.token_times = Some(TokenTimes {
+                        issued: token.issued,
+                        last_used: token.last_used,
+                    });
                 }
+                Ok(None) => {}
+                Err(err) => warn!(
+                    "Ignoring the saved token, which could not be loaded: {}",
+                    self.in_profile(err)
+                ),
             }
-        } else {
-            // not present, so create and protect.
-            let f = File::create(path)?;
-            f.set_permissions(Permissions::from_mode(0o600))?;
-            info!("save file created at '{}'", path.to_string_lossy());
         }
+        Ok(Some(store))
+    }
+
+    /// Use an access token that was retrieved elsewhere. It replaces any token already set, and
+    /// is treated as freshly issued.
+    pub fn set_access_token(
+        mut self,
+        access_token: impl Into<String>,
+        token_secret: impl Into<String>,
+    ) -> Self {
+        self.init_state = FullAuth {
+            access_token: access_token.into(),
+            token_secret: token_secret.into(),
+        };
+        self.token_times = None;
+        self.use_saved_token = false;
+        self
+    }
 
+    /// Read settings from environment variables named `{prefix}_{NAME}`, where NAME is one of
+    /// `CONSUMER_KEY`, `CONSUMER_SECRET`, `ACCESS_TOKEN`, `TOKEN_SECRET`, `REQUEST_TOKEN_URL`,
+    /// `USER_AUTHORIZATION_URL`, `ACCESS_TOKEN_URL`, `RENEW_ACCESS_TOKEN_URL`,
+    /// `REVOKE_ACCESS_TOKEN_URL`, and `CALLBACK_URL`.
+    ///
+    /// Like every other setter, this replaces values set by earlier calls, and is replaced by later
+    /// ones. Variables that are unset or empty leave earlier values alone, so
+    /// `use_secrets_file(path)?.use_env("ETRADE")?` lets the environment override the file.
+    /// `ACCESS_TOKEN` and `TOKEN_SECRET` must be set together.
+    pub fn use_env(self, prefix: &str) -> Result<Self> {
+        self.use_env_with(prefix, &|name| std::env::var(name))
+    }
+
+    // `use_env()`, with the variables from `lookup`, so that tests don't change the environment
+    // that other tests are reading.
+    pub(crate) fn use_env_with(mut self, prefix: &str, lookup: EnvLookup) -> Result<Self> {
+        let var = |name: &str| env_var(lookup, &format!("{}_{}", prefix, name));
+
+        if let Some(key) = var("CONSUMER_KEY")? {
+            self = self.set_consumer_key(key);
+        }
+        if let Some(secret) = var("CONSUMER_SECRET")? {
+            self = self.set_consumer_secret(secret);
+        }
+        match (var("ACCESS_TOKEN")?, var("TOKEN_SECRET")?) {
+            (Some(token), Some(secret)) => self = self.set_access_token(token, secret),
+            (Some(_), None) => {
+                return Err(OagainError::MissingEnvVar(format!(
+                    "{}_TOKEN_SECRET",
+                    prefix
+                )))
+            }
+            (None, Some(_)) => {
+                return Err(OagainError::MissingEnvVar(format!(
+                    "{}_ACCESS_TOKEN",
+                    prefix
+                )))
+            }
+            (None, None) => {}
+        }
+
+        let url_setters: [(&str, UrlSetter); 6] = [
+            ("REQUEST_TOKEN_URL", Builder::set_request_token_url),
+            (
+                "USER_AUTHORIZATION_URL",
+                Builder::set_user_authorization_url,
+            ),
+            ("ACCESS_TOKEN_URL", Builder::set_access_token_url),
+            (
+                "RENEW_ACCESS_TOKEN_URL",
+                Builder::set_renew_access_token_url,
+            ),
+            (
+                "REVOKE_ACCESS_TOKEN_URL",
+                Builder::set_revoke_access_token_url,
+            ),
+            ("CALLBACK_URL", Builder::set_callback_url),
+        ];
+        for (name, set_url) in url_setters {
+            if let Some(url) = var(name)? {
+                let bad_var = || OagainError::BadEnvVar(format!("{}_{}", prefix, name));
+                self = set_url(self, Url::parse(&url).map_err(|_| bad_var())?)?;
+            }
+        }
         Ok(self)
     }
+
+    /// Keep the access token in `store`. If the store holds a token at `build()`, it replaces any
+    /// token set before this call.
+    pub fn use_token_store(self, store: impl TokenStore + 'static) -> Self {
+        self.set_token_store(StoreSetting::Other(Box::new(store)))
+    }
+
+    fn set_token_store(mut self, store: StoreSetting) -> Self {
+        self.token_store = Some(store);
+        self.use_saved_token = true;
+        self
+    }
+
+    /// Keep the access token in a TOML file at `path`. Same as `use_token_store(TomlFileStore)`,
+    /// with the builder's profile.
+    pub fn use_save_file(self, path: impl AsRef<Path>) -> Result<Self> {
+        let store = TomlFileStore::new(path)?;
+        Ok(self.set_token_store(StoreSetting::SaveFile(store)))
+    }
+
+    /// Keep the access token in a file at `path`, encrypted with a key derived from `passphrase`.
+    /// Same as `use_token_store(EncryptedFileStore)`.
+    pub fn use_encrypted_save_file(
+        self,
+        path: impl AsRef<Path>,
+        passphrase: Passphrase,
+    ) -> Result<Self> {
+        let store = EncryptedFileStore::new(path, passphrase)?;
+        Ok(self.set_token_store(StoreSetting::EncryptedSaveFile(store)))
+    }
 }
@@ -1,5 +1,8 @@
+use chrono::Duration;
+
 use crate::consumer::builder::Builder;
 use crate::error::Result;
+use crate::token_lifetime::{DailyCutoff, IdleTimeout};
 
 pub trait Preset {
     fn setup_builder(self, builder: Builder) -> Result<Builder>;
@@ -13,6 +16,12 @@ impl Preset for ETradePreset {
             .set_request_token_url("https://api.etrade.com/oauth/request_token")?
             .set_user_authorization_url("https://us.etrade.com/e/t/etws/authorize")?
             .set_access_token_url("https://api.etrade.com/oauth/access_token")?
+            .set_renew_access_token_url("https://api.etrade.com/oauth/renew_access_token")?
+            .set_revoke_access_token_url("https://api.etrade.com/oauth/revoke_access_token")?
+            .set_token_lifetime_policy((
+                IdleTimeout(Duration::hours(2)),
+                DailyCutoff::midnight(chrono_tz::US::Eastern),
+            ))
             .set_user_auth_key_param_name("key")
             .set_user_auth_token_param_name("token"))
     }
@@ -0,0 +1,226 @@
+//! A temporary HTTP listener on 127.0.0.1 that the provider redirects the user to after
+//! authorization, so the verifier doesn't have to be copied by hand.
+
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+
+use log::debug;
+use url::Url;
+
+use crate::constants::{OAUTH_TOKEN_PARAM_NAME, OAUTH_VERIFIER_PARAM_NAME};
+use crate::error::{OagainError, Result};
+
+const CALLBACK_PATH: &str = "/callback";
+// How often to check for a connection while waiting.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Listens on 127.0.0.1 for the provider's redirect, which carries `oauth_token` and
+/// `oauth_verifier`. Its [url](CallbackServer::url) is sent as `oauth_callback`.
+#[derive(Debug)]
+pub struct CallbackServer {
+    listener: TcpListener,
+    url: Url,
+}
+
+impl CallbackServer {
+    /// Listens on `port`, or on any free port if it's 0.
+    pub fn bind(port: u16) -> Result<CallbackServer> {
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        listener.set_nonblocking(true)?;
+        let port = listener.local_addr()?.port();
+        let url = Url::parse(&format!("http://127.0.0.1:{}{}", port, CALLBACK_PATH))?;
+        Ok(CallbackServer { listener, url })
+    }
+
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Waits up to `timeout` for the redirect, and returns its `oauth_verifier`. Only a redirect
+    /// whose `oauth_token` is `request_token` ends the wait. Anything on this machine can reach
+    /// the server, so redirects for other tokens get a 400, requests for other paths (e.g., the
+    /// browser asking for a favicon) get a 404, and connections that fail (e.g., a preconnect
+    /// that is closed without a request) are logged; none of them end the wait.
+    pub fn wait_for_verifier(&self, request_token: &str, timeout: Duration) -> Result<String> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if Instant::now() >= deadline {
+                return Err(OagainError::CallbackError(
+                    "timed out waiting for the provider's redirect".to_string(),
+                ));
+            }
+            match self.listener.accept() {
+                Ok((stream, _)) => match handle_request(stream, request_token) {
+                    Ok(Some(result)) => return result,
+                    Ok(None) => {}
+                    Err(err) => debug!("Callback server dropped a connection: {}", err),
+                },
+                Err(err) if err.kind() == ErrorKind::WouldBlock => {
+                    std::thread::sleep(POLL_INTERVAL);
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+}
+
+// Answers one request. Returns None if it wasn't the callback for `request_token`, or an error if
+// the connection failed before it could be read.
+fn handle_request(mut stream: TcpStream, request_token: &str) -> Result<Option<Result<String>>> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Drain the headers, so the browser sees a clean response.
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim_end().is_empty() {
+            break;
+        }
+    }
+
+    let target = request_line.split_whitespace().nth(1).unwrap_or_default();
+    debug!("Callback server received {}", target);
+    let url = Url::parse(&format!("http://127.0.0.1{}", target))?;
+    if url.path() != CALLBACK_PATH {
+        write_response(&mut stream, "404 Not Found", "Not found.")?;
+        return Ok(None);
+    }
+
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.into_owned())
+    };
+    if param(OAUTH_TOKEN_PARAM_NAME).as_deref() != Some(request_token) {
+        let message = OagainError::CallbackTokenMismatch.to_string();
+        write_response(&mut stream, "400 Bad Request", &message)?;
+        return Ok(None);
+    }
+    let result = param(OAUTH_VERIFIER_PARAM_NAME).ok_or_else(|| {
+        OagainError::CallbackError(
+            "the redirect has no oauth_verifier; authorization may have been denied".to_string(),
+        )
+    });
+    let written = match &result {
+        Ok(_) => write_response(
+            &mut stream,
+            "200 OK",
+            "Authorization is complete. You can close this window.",
+        ),
+        Err(err) => write_response(&mut stream, "400 Bad Request", &err.to_string()),
+    };
+    // The redirect was received, so the result stands even if the browser has gone.
+    if let Err(err) = written {
+        debug!("Callback server could not answer the redirect: {}", err);
+    }
+    Ok(Some(result))
+}
+
+fn write_response(stream: &mut TcpStream, status: &str, message: &str) -> Result<()> {
+    let body = format!(
+        "<!DOCTYPE html>\n<html><body><p>{}</p></body></html>\n",
+        message
+    );
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+    use std::net::TcpStream;
+    use std::time::Duration;
+
+    use crate::consumer::callback_server::CallbackServer;
+    use crate::error::OagainError;
+
+    // Plays the part of the browser following the provider's redirect. Returns the statuses of
+    // the responses to each request, which are sent in order.
+    fn browse(server: &CallbackServer, paths: &[&str]) -> std::thread::JoinHandle<Vec<u16>> {
+        let urls: Vec<_> = paths
+            .iter()
+            .map(|p| server.url().join(p).unwrap())
+            .collect();
+        std::thread::spawn(move || {
+            urls.into_iter()
+                .map(|url| reqwest::blocking::get(url).unwrap().status().as_u16())
+                .collect()
+        })
+    }
+
+    #[test]
+    fn verifier_is_captured() {
+        let server = CallbackServer::bind(0).unwrap();
+        let browser = browse(
+            &server,
+            &[
+                "/favicon.ico",
+                "/callback?oauth_token=hh5s93j4hdidpola&oauth_verifier=hfdp7dh39dks9884",
+            ],
+        );
+
+        let verifier = server
+            .wait_for_verifier("hh5s93j4hdidpola", Duration::from_secs(5))
+            .unwrap();
+        assert_eq!("hfdp7dh39dks9884", verifier);
+        assert_eq!(vec![404, 200], browser.join().unwrap());
+    }
+
+    #[test]
+    fn failed_connections_are_ignored() {
+        let server = CallbackServer::bind(0).unwrap();
+        let addr = server.listener.local_addr().unwrap();
+        // A preconnect that is closed without a request, then requests that can't be parsed.
+        drop(TcpStream::connect(addr).unwrap());
+        for request_line in ["GET * HTTP/1.1", "GET http://[::1 HTTP/1.1"] {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            write!(stream, "{}\r\n\r\n", request_line).unwrap();
+        }
+        let browser = browse(
+            &server,
+            &["/callback?oauth_token=hh5s93j4hdidpola&oauth_verifier=hfdp7dh39dks9884"],
+        );
+
+        let verifier = server
+            .wait_for_verifier("hh5s93j4hdidpola", Duration::from_secs(5))
+            .unwrap();
+        assert_eq!("hfdp7dh39dks9884", verifier);
+        assert_eq!(vec![200], browser.join().unwrap());
+    }
+
+    #[test]
+    fn token_must_match() {
+        let server = CallbackServer::bind(0).unwrap();
+        let browser = browse(
+            &server,
+            &[
+                "/callback?oauth_token=someone-elses&oauth_verifier=hfdp7dh39dks9884",
+                "/callback?oauth_token=hh5s93j4hdidpola&oauth_verifier=hfdp7dh39dks9884",
+            ],
+        );
+
+        let verifier = server
+            .wait_for_verifier("hh5s93j4hdidpola", Duration::from_secs(5))
+            .unwrap();
+        assert_eq!("hfdp7dh39dks9884", verifier);
+        assert_eq!(vec![400, 200], browser.join().unwrap());
+    }
+
+    #[test]
+    fn times_out() {
+        let server = CallbackServer::bind(0).unwrap();
+        assert!(matches!(
+            server.wait_for_verifier("hh5s93j4hdidpola", Duration::from_millis(100)),
+            Err(OagainError::CallbackError(_))
+        ));
+    }
+}
@@ -0,0 +1,396 @@
+//! The consumer's own settings, kept apart from any user's tokens, so that one configuration can
+//! sign requests for many users.
+
+use std::iter::once;
+
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
+use log::{debug, warn};
+use reqwest::blocking::{Client, Request};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::constants::*;
+use crate::consumer::request_body::RequestBody;
+use crate::consumer::response::SignedResponse;
+use crate::consumer::state::{ConsumerState, PendingAuthorization};
+use crate::error::{OagainError, Result};
+use crate::nonce_provider::NonceProvider;
+use crate::parameters::{try_decode_params_string, ParamPair};
+use crate::signing::{concat_request_elements, SignatureMethod};
+use crate::token_lifetime::{TokenLifetimePolicy, TokenStatus};
+use crate::token_store::SavedToken;
+use crate::util::BoolToOption;
+
+/// A user's access token.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessToken {
+    pub token: String,
+    pub secret: String,
+}
+
+impl From<AccessToken> for ConsumerState {
+    fn from(token: AccessToken) -> Self {
+        ConsumerState::FullAuth {
+            access_token: token.token,
+            token_secret: token.secret,
+        }
+    }
+}
+
+impl From<&SavedToken> for AccessToken {
+    fn from(token: &SavedToken) -> Self {
+        AccessToken {
+            token: token.access_token.clone(),
+            secret: token.token_secret.clone(),
+        }
+    }
+}
+
+/// Everything about the consumer that doesn't depend on the user: the consumer key and secret,
+/// the signature method, and the provider's URLs. Built with `Builder::build_config()`.
+///
+/// Where a [Consumer](crate::Consumer) holds one user's tokens, this takes the user's
+/// [AccessToken] with each request, so one configuration can serve any number of users, from any
+/// number of threads.
+#[derive(Debug)]
+pub struct ConsumerConfig<NP: NonceProvider> {
+    pub(crate) consumer_key: String,
+    pub(crate) consumer_secret: String,
+    pub(crate) signature_method: Box<dyn SignatureMethod>,
+    pub(crate) allow_insecure_plaintext: bool,
+    pub(crate) use_body_hash: bool,
+    pub(crate) nonce_provider: NP,
+
+    pub(crate) request_token_url: Url,
+    pub(crate) user_authorization_url: Url,
+    pub(crate) access_token_url: Url,
+    pub(crate) renew_access_token_url: Option<Url>,
+    pub(crate) revoke_access_token_url: Option<Url>,
+    pub(crate) callback_url: Option<Url>,
+
+    pub(crate) user_auth_key_param_name: String,
+    pub(crate) user_auth_token_param_name: String,
+
+    pub(crate) token_lifetime: Box<dyn TokenLifetimePolicy>,
+}
+
+impl<NP: NonceProvider> ConsumerConfig<NP> {
+    pub fn consumer_key(&self) -> &str {
+        &self.consumer_key
+    }
+
+    /// Adds the `Authorization` header for `token` to `request`. A form-encoded body is included
+    /// in the signature. Other bodies are covered by `oauth_body_hash`, so streaming bodies
+    /// can't be signed.
+    pub fn sign(&self, token: &AccessToken, request: &mut Request) -> Result<()> {
+        let body = match request.body() {
+            None => RequestBody::Empty,
+            Some(body) => {
+                let bytes = body.as_bytes().ok_or(OagainError::StreamingBody)?;
+                let content_type = request
+                    .headers()
+                    .get(CONTENT_TYPE)
+                    .and_then(|value| value.to_str().ok())
+                    .unwrap_or("application/octet-stream");
+                if content_type.starts_with("application/x-www-form-urlencoded") {
+                    RequestBody::Form(
+                        url::form_urlencoded::parse(bytes)
+                            .map(|(name, value)| ParamPair::pair(name, value))
+                            .collect(),
+                    )
+                } else {
+                    RequestBody::Raw {
+                        content_type: content_type.to_string(),
+                        bytes: bytes.to_vec(),
+                    }
+                }
+            }
+        };
+
+        let header = self.sign_request_from_components(
+            &token.clone().into(),
+            None,
+            request.method().as_str(),
+            request.url(),
+            &body,
+        )?;
+        let value = HeaderValue::from_str(&header)
+            .map_err(|_| OagainError::BadHeader(AUTHORIZATION.to_string()))?;
+        request.headers_mut().insert(AUTHORIZATION, value);
+        Ok(())
+    }
+
+    /// Signs `request` for `token`, and sends it. Non-2xx responses are returned as errors.
+    pub fn send(&self, token: &AccessToken, mut request: Request) -> Result<SignedResponse> {
+        self.sign(token, &mut request)?;
+        SignedResponse::read(Client::new().execute(request)?)?.error_for_status()
+    }
+
+    /// The status of a user's saved token, according to the token lifetime policy.
+    pub fn token_status(&self, token: &SavedToken, now: DateTime<Utc>) -> TokenStatus {
+        self.token_lifetime
+            .status(token.issued, token.last_used, now)
+    }
+
+    /// Starts authorizing a user by retrieving a request token.
+    pub fn retrieve_request_token(&self) -> Result<PendingAuthorization> {
+        let (request_token, token_secret) = self.retrieve_token_pair(
+            &ConsumerState::NoAuth,
+            self.callback_url.as_ref(),
+            &self.request_token_url,
+        )?;
+        Ok(PendingAuthorization {
+            request_token: request_token.ok_or(OagainError::MissingRequestToken)?,
+            token_secret: token_secret.ok_or(OagainError::MissingTokenSecret)?,
+        })
+    }
+
+    /// The URL where the user authorizes `pending`.
+    pub fn user_auth_url(&self, pending: &PendingAuthorization) -> Url {
+        let mut url = self.user_authorization_url.clone();
+        url.query_pairs_mut()
+            .append_pair(&self.user_auth_token_param_name, &pending.request_token)
+            .append_pair(&self.user_auth_key_param_name, &self.consumer_key);
+        url
+    }
+
+    /// Finishes authorizing a user, with the `verifier` they received for `pending`.
+    pub fn retrieve_access_token(
+        &self,
+        pending: &PendingAuthorization,
+        verifier: impl AsRef<str>,
+    ) -> Result<AccessToken> {
+        let state = ConsumerState::UserAuth {
+            request_token: pending.request_token.clone(),
+            token_secret: pending.token_secret.clone(),
+            verification_code: verifier.as_ref().to_string(),
+        };
+        let (token, secret) = self.retrieve_token_pair(&state, None, &self.access_token_url)?;
+        Ok(AccessToken {
+            token: token.ok_or(OagainError::MissingAccessToken)?,
+            secret: secret.ok_or(OagainError::MissingTokenSecret)?,
+        })
+    }
+
+    // Sends a token request, and returns the `oauth_token` and `oauth_token_secret` from the
+    // response. `callback_url` is only sent with a request for a request token.
+    pub(crate) fn retrieve_token_pair(
+        &self,
+        state: &ConsumerState,
+        callback_url: Option<&Url>,
+        url: &Url,
+    ) -> Result<(Option<String>, Option<String>)> {
+        let response = self.send_request(
+            state,
+            callback_url,
+            Method::GET,
+            url,
+            &RequestBody::Empty,
+            HeaderMap::new(),
+        )?;
+        let response_str = response.text()?;
+        debug!("token response: {}", response_str);
+
+        let mut token = None;
+        let mut token_secret = None;
+        // The response didn't come from us, so it may be malformed.
+        for param in try_decode_params_string(response_str).ok_or(OagainError::BadTokenResponse)? {
+            if param.name == OAUTH_TOKEN_PARAM_NAME {
+                token = param.value;
+            } else if param.name == OAUTH_TOKEN_SECRET_PARAM_NAME {
+                token_secret = param.value;
+            }
+        }
+        Ok((token, token_secret))
+    }
+
+    pub(crate) fn send_request(
+        &self,
+        state: &ConsumerState,
+        callback_url: Option<&Url>,
+        method: Method,
+        url: &Url,
+        body: &RequestBody,
+        mut headers: HeaderMap,
+    ) -> Result<SignedResponse> {
+        let auth_header =
+            self.sign_request_from_components(state, callback_url, method.as_str(), url, body)?;
+        debug!("auth_header: {}", auth_header);
+        if let Some(content_type) = body.content_type() {
+            if !headers.contains_key(CONTENT_TYPE) {
+                let value = HeaderValue::from_str(content_type)
+                    .map_err(|_| OagainError::BadHeader(content_type.to_string()))?;
+                headers.insert(CONTENT_TYPE, value);
+            }
+        }
+        // TODO: reuse these clients.
+        let client = Client::builder().build()?;
+        let mut request = client
+            .request(method, url.clone())
+            .headers(headers)
+            .header(AUTHORIZATION, auth_header);
+        if let Some(bytes) = body.to_bytes() {
+            request = request.body(bytes);
+        }
+        SignedResponse::read(request.send()?)?.error_for_status()
+    }
+
+    /// Returns the Authorization header for the request. The params from a form-encoded body
+    /// are included in the signature. Other bodies are covered by `oauth_body_hash`.
+    pub(crate) fn sign_request_from_components(
+        &self,
+        state: &ConsumerState,
+        callback_url: Option<&Url>,
+        method: impl AsRef<str>,
+        url: &Url,
+        body: &RequestBody,
+    ) -> Result<String> {
+        let (timestamp, nonce) = self.nonce()?;
+        debug!("timestamp, nonce: {}, {}", timestamp, nonce);
+        let mut standard_params = self.oauth_param_list(state, callback_url, timestamp, nonce);
+        if let Some(body_hash) = self.body_hash(body) {
+            standard_params.push(ParamPair::pair(OAUTH_BODY_HASH_PARAM_NAME, body_hash));
+        }
+        debug!("standard_params: {:?}", standard_params);
+
+        let other_params = url
+            .query_pairs()
+            .map(|(name, value)| ParamPair::pair(name, value))
+            .collect::<Vec<_>>();
+
+        let param_iter = standard_params
+            .iter()
+            .chain(other_params.iter())
+            .chain(body.form_params().iter());
+
+        let string_to_sign = if self.signature_method.uses_base_string() {
+            concat_request_elements(method.as_ref(), url, param_iter.cloned())
+        } else {
+            self.check_plaintext_url(url)?;
+            String::default()
+        };
+        debug!("string_to_sign: {}", string_to_sign);
+
+        debug!("signature_method: {:?}", self.signature_method);
+        let signature = self.signature_method.sign(
+            &self.consumer_secret,
+            state.token_secret().unwrap_or_default(),
+            &string_to_sign,
+        )?;
+
+        let header = self.oauth_header(&standard_params, signature);
+        Ok(header)
+    }
+
+    // The body hash is only sent for bodies that aren't form-encoded. (Body Hash 3.1)
+    fn body_hash(&self, body: &RequestBody) -> Option<String> {
+        let RequestBody::Raw { bytes, .. } = body else {
+            return None;
+        };
+        if !self.use_body_hash {
+            return None;
+        }
+        let body_hash = self.signature_method.body_hash(bytes);
+        if body_hash.is_none() {
+            warn!(
+                "Signature method {} does not support oauth_body_hash. Body is unprotected.",
+                self.signature_method.name()
+            );
+        }
+        body_hash
+    }
+
+    // PLAINTEXT exposes the secrets in the request, so it is refused over anything but https,
+    // unless the builder explicitly allowed it.
+    fn check_plaintext_url(&self, url: &Url) -> Result<()> {
+        if url.scheme() != "https" {
+            if !self.allow_insecure_plaintext {
+                return Err(OagainError::InsecurePlainText(url.to_string()));
+            }
+            warn!(
+                "Sending PLAINTEXT signature over an insecure connection: {}",
+                url
+            );
+        }
+        Ok(())
+    }
+
+    pub(crate) fn oauth_param_list(
+        &self,
+        state: &ConsumerState,
+        callback_url: Option<&Url>,
+        timestamp: u32,
+        nonce: impl AsRef<str>,
+    ) -> Vec<ParamPair> {
+        let (include_callback, include_token, include_verifier) = match state {
+            ConsumerState::NoAuth => (true, false, false),
+            ConsumerState::RequestToken { .. } => (false, true, false),
+            ConsumerState::UserAuth { .. } => (false, true, true),
+            ConsumerState::FullAuth { .. } => (false, true, false),
+        };
+        let pair_descriptions: &[(&'static str, &dyn Fn() -> Option<String>)] = &[
+            (OAUTH_CONSUMER_KEY_PARAM_NAME, &|| {
+                Some(self.consumer_key.clone())
+            }),
+            (OAUTH_SIGNATURE_METHOD_PARAM_NAME, &|| {
+                Some(self.signature_method.name().to_string())
+            }),
+            (OAUTH_TIMESTAMP_PARAM_NAME, &|| Some(timestamp.to_string())),
+            (OAUTH_NONCE_PARAM_NAME, &|| {
+                nonce.as_ref().to_string().into()
+            }),
+            (OAUTH_VERSION_PARAM_NAME, &|| {
+                OAUTH_VERSION_VALUE.to_string().into()
+            }),
+            (OAUTH_CALLBACK_PARAM_NAME, &|| {
+                include_callback.option(
+                    callback_url.map_or(OAUTH_CALLBACK_OOB_VALUE.to_string(), Url::to_string),
+                )
+            }),
+            (OAUTH_TOKEN_PARAM_NAME, &|| {
+                // TODO: bad unwrap
+                // TODO: need to use access_token sometimes.
+                include_token.option_with(|| state.token().unwrap().to_string())
+            }),
+            (OAUTH_VERIFIER_PARAM_NAME, &|| {
+                // TODO: bad unwrap
+                include_verifier.option_with(|| state.verification_code().unwrap().to_string())
+            }),
+        ];
+
+        pair_descriptions
+            .iter()
+            .fold(Vec::default(), |mut acc, (name, value_func)| {
+                if let Some(val) = value_func() {
+                    acc.push(ParamPair::pair(*name, val));
+                }
+                acc
+            })
+    }
+
+    //----------------------------------------------------------------------
+
+    pub(crate) fn nonce(&self) -> Result<(u32, String)> {
+        self.nonce_provider.nonce()
+    }
+
+    pub(crate) fn oauth_header(
+        &self,
+        param_pairs: &[ParamPair],
+        signature: impl AsRef<str>,
+    ) -> String {
+        let signature_pair = ParamPair::pair(OAUTH_SIGNATURE_PARAM_NAME, signature.as_ref());
+        format!(
+            "OAuth {}",
+            param_pairs
+                .iter()
+                .sorted()
+                .chain(once(&signature_pair))
+                .map(|pp| pp.to_wrapped_string())
+                .join(", ")
+        )
+    }
+}
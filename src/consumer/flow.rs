@@ -0,0 +1,270 @@
+//! A typestate API for the three-legged flow, where each step consumes the previous one, so
+//! steps can't be taken out of order.
+
+use std::fmt::{Debug, Display, Formatter};
+
+use reqwest::header::HeaderMap;
+use reqwest::Method;
+use url::Url;
+
+use crate::consumer::state::{ConsumerState, PendingAuthorization};
+use crate::consumer::{AccessToken, Consumer, SignedRequestBuilder, UserAuthorizer};
+use crate::error::{OagainError, Result};
+use crate::nonce_provider::NonceProvider;
+
+/// A failed step, which hands back the state it started from, so the step can be retried.
+#[derive(Debug)]
+pub struct FlowError<S> {
+    pub error: OagainError,
+    pub state: S,
+}
+
+impl<S> Display for FlowError<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.error, f)
+    }
+}
+
+impl<S: Debug> std::error::Error for FlowError<S> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl<S> From<FlowError<S>> for OagainError {
+    fn from(err: FlowError<S>) -> Self {
+        err.error
+    }
+}
+
+/// The start of the flow. Ready to retrieve a request token.
+///
+/// Steps can only be taken in order, so this doesn't compile:
+///
+/// ```compile_fail
+/// fn authorize(consumer: oagain::BasicConsumer) -> oagain::Result<()> {
+///     let flow = oagain::Unauthorized::new(consumer);
+///     flow.set_verification_code("hfdp7dh39dks9884");
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Unauthorized<NP: NonceProvider> {
+    // Boxed, so that results carrying a state stay small.
+    consumer: Box<Consumer<NP>>,
+}
+
+/// The request token has been retrieved. Ready for the user to authorize it.
+#[derive(Debug)]
+pub struct RequestTokenHeld<NP: NonceProvider> {
+    consumer: Box<Consumer<NP>>,
+    pending: Box<PendingAuthorization>,
+}
+
+/// The user has authorized the request token. Ready to retrieve the access token.
+#[derive(Debug)]
+pub struct Verified<NP: NonceProvider> {
+    consumer: Box<Consumer<NP>>,
+}
+
+/// The access token has been retrieved. Ready for signed requests, which are all it offers, so
+/// the flow can't be restarted or the token revoked from here. `into_consumer()` gives back the
+/// whole [Consumer].
+///
+/// ```compile_fail
+/// fn revoke(consumer: oagain::BasicConsumer) {
+///     if let Ok(flow) = consumer.into_authorized() {
+///         flow.revoke();
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Authorized<NP: NonceProvider> {
+    consumer: Box<Consumer<NP>>,
+}
+
+impl<NP: NonceProvider> Consumer<NP> {
+    /// Starts the typestate flow, unless the consumer already has an access token.
+    pub fn into_authorized(self) -> std::result::Result<Authorized<NP>, Unauthorized<NP>> {
+        if self.is_fully_authed() {
+            Ok(Authorized {
+                consumer: Box::new(self),
+            })
+        } else {
+            Err(Unauthorized::new(self))
+        }
+    }
+}
+
+impl<NP: NonceProvider> Unauthorized<NP> {
+    /// Starts the flow with `consumer`, which forgets any token it has.
+    pub fn new(consumer: Consumer<NP>) -> Unauthorized<NP> {
+        consumer.restart(ConsumerState::NoAuth);
+        Unauthorized {
+            consumer: Box::new(consumer),
+        }
+    }
+
+    pub fn retrieve_request_token(
+        self,
+    ) -> std::result::Result<RequestTokenHeld<NP>, FlowError<Self>> {
+        match self.consumer.retrieve_request_token() {
+            Ok(()) => Ok(RequestTokenHeld {
+                // unwrap: retrieving the request token leaves it pending.
+                pending: Box::new(self.consumer.pending_authorization().unwrap()),
+                consumer: self.consumer,
+            }),
+            Err(error) => Err(FlowError { error, state: self }),
+        }
+    }
+}
+
+impl<NP: NonceProvider> RequestTokenHeld<NP> {
+    /// Picks up the flow with a request token retrieved elsewhere.
+    pub fn resume(consumer: Consumer<NP>, pending: PendingAuthorization) -> RequestTokenHeld<NP> {
+        consumer.restart(pending.clone().into());
+        RequestTokenHeld {
+            consumer: Box::new(consumer),
+            pending: Box::new(pending),
+        }
+    }
+
+    /// A snapshot of the request token, for resuming the flow later.
+    pub fn pending_authorization(&self) -> PendingAuthorization {
+        *self.pending.clone()
+    }
+
+    pub fn request_token(&self) -> &str {
+        &self.pending.request_token
+    }
+
+    /// The URL where the user authorizes the request token.
+    pub fn user_auth_url(&self) -> Result<Url> {
+        self.consumer.make_user_auth_url()
+    }
+
+    /// Uses the verification code the user received after authorizing.
+    pub fn set_verification_code(self, code: impl AsRef<str>) -> Verified<NP> {
+        let pending = *self.pending;
+        self.consumer.restart(ConsumerState::UserAuth {
+            request_token: pending.request_token,
+            token_secret: pending.token_secret,
+            verification_code: code.as_ref().to_string(),
+        });
+        Verified {
+            consumer: self.consumer,
+        }
+    }
+
+    /// Gets the verification code from `authorizer`.
+    pub fn authorize_with(
+        self,
+        authorizer: &mut dyn UserAuthorizer,
+    ) -> std::result::Result<Verified<NP>, FlowError<Self>> {
+        let code = self.user_auth_url().and_then(|url| {
+            let request_token = self.request_token().to_string();
+            authorizer.authorize(&url, &request_token)
+        });
+        match code {
+            Ok(code) => Ok(self.set_verification_code(code)),
+            Err(error) => Err(FlowError { error, state: self }),
+        }
+    }
+}
+
+impl<NP: NonceProvider> Verified<NP> {
+    pub fn retrieve_access_token(self) -> std::result::Result<Authorized<NP>, FlowError<Self>> {
+        match self.consumer.retrieve_access_token() {
+            Ok(()) => Ok(Authorized {
+                consumer: self.consumer,
+            }),
+            Err(error) => Err(FlowError { error, state: self }),
+        }
+    }
+}
+
+// The request methods of [Consumer], and nothing that changes where the flow is.
+impl<NP: NonceProvider> Authorized<NP> {
+    pub fn into_consumer(self) -> Consumer<NP> {
+        *self.consumer
+    }
+
+    pub fn access_token(&self) -> Option<AccessToken> {
+        self.consumer.access_token()
+    }
+
+    pub fn request(&self, method: Method, url: &Url) -> SignedRequestBuilder<'_, NP> {
+        self.consumer.request(method, url)
+    }
+
+    pub fn get(&self, url: &Url) -> Result<String> {
+        self.consumer.get(url)
+    }
+
+    pub fn post_form<N, V>(
+        &self,
+        url: &Url,
+        params: impl IntoIterator<Item = (N, V)>,
+    ) -> Result<String>
+    where
+        N: Into<String>,
+        V: Into<String>,
+    {
+        self.consumer.post_form(url, params)
+    }
+
+    pub fn put_form<N, V>(
+        &self,
+        url: &Url,
+        params: impl IntoIterator<Item = (N, V)>,
+    ) -> Result<String>
+    where
+        N: Into<String>,
+        V: Into<String>,
+    {
+        self.consumer.put_form(url, params)
+    }
+
+    pub fn patch_form<N, V>(
+        &self,
+        url: &Url,
+        params: impl IntoIterator<Item = (N, V)>,
+    ) -> Result<String>
+    where
+        N: Into<String>,
+        V: Into<String>,
+    {
+        self.consumer.patch_form(url, params)
+    }
+
+    pub fn delete(&self, url: &Url) -> Result<String> {
+        self.consumer.delete(url)
+    }
+
+    pub fn head(&self, url: &Url) -> Result<HeaderMap> {
+        self.consumer.head(url)
+    }
+
+    pub fn send_form<N, V>(
+        &self,
+        method: Method,
+        url: &Url,
+        params: impl IntoIterator<Item = (N, V)>,
+    ) -> Result<String>
+    where
+        N: Into<String>,
+        V: Into<String>,
+    {
+        self.consumer.send_form(method, url, params)
+    }
+
+    pub fn send_body(
+        &self,
+        method: Method,
+        url: &Url,
+        content_type: impl Into<String>,
+        body: impl Into<Vec<u8>>,
+    ) -> Result<String> {
+        self.consumer.send_body(method, url, content_type, body)
+    }
+}
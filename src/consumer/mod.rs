@@ -1,48 +1,74 @@
-use std::fs::File;
-use std::io::Write;
-use std::iter::once;
-use std::path::PathBuf;
-
-use chrono::{Datelike, Timelike, Utc};
-use itertools::Itertools;
-use log::{debug, error};
-use reqwest::blocking::Client;
-use reqwest::blocking::Response;
-use toml::Value;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use log::{debug, error, info, warn};
+use reqwest::header::HeaderMap;
+use reqwest::Method;
 use url::Url;
 
+pub use authorizer::{BrowserPrompt, Headless, LoopbackCallback, TerminalPrompt, UserAuthorizer};
 pub use builder::preset::ETradePreset;
+pub use callback_server::CallbackServer;
+pub use consumer_config::{AccessToken, ConsumerConfig};
+pub use flow::{Authorized, FlowError, RequestTokenHeld, Unauthorized, Verified};
+pub use request::SignedRequestBuilder;
+use request_body::RequestBody;
+pub use response::SignedResponse;
 use state::ConsumerState;
+pub use state::PendingAuthorization;
 
-use crate::constants::*;
 pub use crate::consumer::builder::Builder;
 use crate::error::{OagainError, Result};
 use crate::nonce_provider::{BasicNonce, NonceProvider, SystemEpochProvider};
-use crate::parameters::{decode_params_string, ParamPair};
-use crate::signing::{concat_request_elements, make_signing_key, sign_string_hmac};
-use crate::util::BoolToOption;
+use crate::parameters::ParamPair;
+use crate::profile::profile_label;
+#[cfg(test)]
+use crate::signing::SignatureMethod;
+use crate::token_lifetime::{TokenLifetimePolicy, TokenStatus, TokenTimes};
+use crate::token_store::{SavedToken, TokenStore};
 
+mod authorizer;
 mod builder;
+mod callback_server;
+mod consumer_config;
+mod flow;
+mod request;
+mod request_body;
+mod response;
 mod state;
+#[cfg(test)]
+pub(crate) mod test_cases;
+
+// A use is only saved once it moves the saved last-use time by this much, so that busy consumers
+// don't rewrite the store on every request.
+const LAST_USE_SAVE_MINUTES: i64 = 1;
 
 /// A basic consumer that uses the standard time-based nonce provider.
 pub type BasicConsumer = Consumer<BasicNonce<SystemEpochProvider>>;
 
+/// Makes signed requests on behalf of one user, authorizing when it needs to.
+///
+/// Requests take `&self`, so a consumer can be shared between threads in an `Arc`. Only one
+/// thread at a time renews the token or asks the user to authorize; the others wait for it.
 #[derive(Debug)]
 pub struct Consumer<NP: NonceProvider> {
-    consumer_key: String,
-    consumer_secret: String,
-    nonce_provider: NP,
-
-    request_token_url: Url,
-    user_authorization_url: Url,
-    access_token_url: Url,
-
-    user_auth_key_param_name: String,
-    user_auth_token_param_name: String,
+    config: ConsumerConfig<NP>,
+    // Also held while renewing the token, so that only one thread does it.
+    user_authorizer: Mutex<Box<dyn UserAuthorizer>>,
+
+    profile: Option<String>,
+    token_store: Option<Box<dyn TokenStore>>,
+    session: Mutex<Session>,
+    // The last-use time in the store. Held while saving, so that saves happen in order.
+    saved_last_use: Mutex<Option<DateTime<Utc>>>,
+}
 
-    save_file: Option<PathBuf>,
+// The user's tokens, which change as the consumer authorizes.
+#[derive(Debug, Default)]
+struct Session {
     state: ConsumerState,
+    token_times: Option<TokenTimes>,
 }
 
 impl<NP: NonceProvider> Consumer<NP> {
@@ -51,83 +77,330 @@ impl<NP: NonceProvider> Consumer<NP> {
     }
 
     pub fn is_fully_authed(&self) -> bool {
-        matches!(self.state, ConsumerState::FullAuth { .. })
-    }
-
-    fn ensure_auth(&mut self) -> Result<()> {
-        if !self.is_fully_authed() {
-            self.retrieve_request_token()?;
-
-            // TODO: pass in an argument to the consumer on whether to open the browser.
-            // TODO: make the messages match what's happening with the browser opening or not.
-            let url = self.make_user_auth_url()?;
-            println!(
-                "Go to the following URL and follow the instructions:\n\n    {}\n\n",
-                url
-            );
-            open::that(url.to_string())?;
-            print!("Input the verification code received from the server: ");
-            std::io::stdout().flush()?;
-            let mut code: String = Default::default();
-            std::io::stdin().read_line(&mut code)?;
-            self.set_verification_code(code.trim())?;
-
-            self.retrieve_access_token()?;
+        matches!(self.session().state, ConsumerState::FullAuth { .. })
+    }
+
+    // The session is only changed by whole assignments, so it's still consistent if a thread
+    // panicked while holding the lock.
+    fn session(&self) -> MutexGuard<'_, Session> {
+        self.session.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // A copy of the state, so that the lock isn't held while sending requests.
+    fn state(&self) -> ConsumerState {
+        self.session().state.clone()
+    }
+
+    // A copy of the state if it holds an access token. Requests are signed with the copy, since
+    // another thread may forget or replace the token after it was checked.
+    fn full_auth(&self) -> Option<ConsumerState> {
+        let state = self.state();
+        matches!(state, ConsumerState::FullAuth { .. }).then_some(state)
+    }
+
+    // The times of the access token, if the consumer has one.
+    fn access_token_times(&self) -> Option<TokenTimes> {
+        let session = self.session();
+        session
+            .token_times
+            .filter(|_| matches!(session.state, ConsumerState::FullAuth { .. }))
+    }
+
+    // Starts over from `state`, with no token times.
+    fn restart(&self, state: ConsumerState) {
+        *self.session() = Session {
+            state,
+            token_times: None,
+        };
+    }
+
+    // Returns the state to sign with, which holds an access token.
+    fn ensure_auth(&self) -> Result<ConsumerState> {
+        if let Some(state) = self.fresh_auth() {
+            return Ok(state);
         }
-        Ok(())
+
+        // Another thread may be renewing or authorizing. Wait for it to finish, then use its
+        // token if it got one.
+        let mut authorizer = self
+            .user_authorizer
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        self.check_token_lifetime();
+        if let Some(state) = self.full_auth() {
+            return Ok(state);
+        }
+
+        // Another process sharing the token store may be authorizing. Wait for it to finish,
+        // then use its token if it got one.
+        let _lock = self
+            .token_store
+            .as_ref()
+            .map(|store| store.lock())
+            .transpose()?;
+        self.reload_saved_token();
+        if let Some(state) = self.full_auth() {
+            return Ok(state);
+        }
+
+        let callback_url = authorizer.prepare()?;
+        self.retrieve_request_token_with_callback(
+            callback_url.as_ref().or(self.config.callback_url.as_ref()),
+        )?;
+
+        let url = self.make_user_auth_url()?;
+        let request_token = self
+            .state()
+            .token()
+            .ok_or(OagainError::MissingRequestToken)?
+            .to_string();
+        let code = authorizer.authorize(&url, &request_token)?;
+        self.set_verification_code(code)?;
+
+        self.retrieve_access_token()?;
+        // A thread that doesn't wait for the authorizer, such as one revoking, may have forgotten
+        // the new token already.
+        self.full_auth().ok_or(OagainError::MissingAccessToken)
+    }
+
+    // A copy of the state if requests can be signed without renewing the token or authorizing.
+    // The state and times are checked under one lock, so they belong to the same token.
+    fn fresh_auth(&self) -> Option<ConsumerState> {
+        let session = self.session();
+        let fresh = matches!(session.state, ConsumerState::FullAuth { .. })
+            && session.token_times.is_none_or(|times| {
+                self.config
+                    .token_lifetime
+                    .status(times.issued, times.last_used, Utc::now())
+                    == TokenStatus::Fresh
+            });
+        fresh.then(|| session.state.clone())
+    }
+
+    // Renews the access token if it has gone inactive, or forgets it if it has expired.
+    fn check_token_lifetime(&self) {
+        let Some(times) = self.access_token_times() else {
+            return;
+        };
+        match self
+            .config
+            .token_lifetime
+            .status(times.issued, times.last_used, Utc::now())
+        {
+            TokenStatus::Fresh => {}
+            TokenStatus::NeedsRenewal => {
+                if let Err(err) = self.renew_access_token() {
+                    warn!(
+                        "Failed to renew the access token{}, so starting over: {}",
+                        profile_label(&self.profile),
+                        err
+                    );
+                    self.forget_access_token();
+                }
+            }
+            TokenStatus::Expired => {
+                info!(
+                    "The access token{} has expired, so starting over.",
+                    profile_label(&self.profile)
+                );
+                self.forget_access_token();
+            }
+        }
+    }
+
+    // Adopts the token in the store, if it's fresh.
+    fn reload_saved_token(&self) {
+        let Some(store) = &self.token_store else {
+            return;
+        };
+        match store.load() {
+            Ok(Some(token)) => {
+                let status =
+                    self.config
+                        .token_lifetime
+                        .status(token.issued, token.last_used, Utc::now());
+                if status == TokenStatus::Fresh {
+                    debug!("Using the access token saved by another process.");
+                    *self
+                        .saved_last_use
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner) = Some(token.last_used);
+                    *self.session() = Session {
+                        state: ConsumerState::FullAuth {
+                            access_token: token.access_token,
+                            token_secret: token.token_secret,
+                        },
+                        token_times: Some(TokenTimes {
+                            issued: token.issued,
+                            last_used: token.last_used,
+                        }),
+                    };
+                }
+            }
+            Ok(None) => {}
+            Err(err) => warn!(
+                "Ignoring the saved token{}, which could not be loaded: {}",
+                profile_label(&self.profile),
+                err
+            ),
+        }
+    }
+
+    fn forget_access_token(&self) {
+        self.restart(ConsumerState::NoAuth);
+    }
+
+    /// Starts building a signed request.
+    pub fn request(&self, method: Method, url: &Url) -> SignedRequestBuilder<'_, NP> {
+        SignedRequestBuilder::new(self, method, url)
+    }
+
+    pub fn get(&self, url: &Url) -> Result<String> {
+        self.send_form(Method::GET, url, Vec::<(String, String)>::new())
+    }
+
+    /// POSTs `params` to `url` as an `application/x-www-form-urlencoded` body.
+    /// The body params are included in the signature. (Spec 9.1.1)
+    pub fn post_form<N, V>(
+        &self,
+        url: &Url,
+        params: impl IntoIterator<Item = (N, V)>,
+    ) -> Result<String>
+    where
+        N: Into<String>,
+        V: Into<String>,
+    {
+        self.send_form(Method::POST, url, params)
     }
 
-    pub fn get(&mut self, url: &Url) -> Result<String> {
-        self.ensure_auth()?;
+    /// PUTs `params` to `url` as an `application/x-www-form-urlencoded` body.
+    pub fn put_form<N, V>(
+        &self,
+        url: &Url,
+        params: impl IntoIterator<Item = (N, V)>,
+    ) -> Result<String>
+    where
+        N: Into<String>,
+        V: Into<String>,
+    {
+        self.send_form(Method::PUT, url, params)
+    }
 
-        let auth_header = self.sign_request_from_components("GET", url)?;
-        debug!("get: auth_header: {}", auth_header);
-        let client = Client::builder().build()?;
-        let response = client
-            .get(url.clone())
-            .header("Authorization", auth_header)
-            .send()?;
+    /// PATCHes `params` to `url` as an `application/x-www-form-urlencoded` body.
+    pub fn patch_form<N, V>(
+        &self,
+        url: &Url,
+        params: impl IntoIterator<Item = (N, V)>,
+    ) -> Result<String>
+    where
+        N: Into<String>,
+        V: Into<String>,
+    {
+        self.send_form(Method::PATCH, url, params)
+    }
 
-        // TODO: check for non-200 result code.
-        debug!("get: response: {:?}", response);
-        let response_str = String::from_utf8(Vec::from(response.bytes()?))?;
+    pub fn delete(&self, url: &Url) -> Result<String> {
+        self.send_form(Method::DELETE, url, Vec::<(String, String)>::new())
+    }
 
+    /// Sends a HEAD request, returning the response headers.
+    pub fn head(&self, url: &Url) -> Result<HeaderMap> {
+        let response = self.request(Method::HEAD, url).send()?;
+        Ok(response.headers().clone())
+    }
+
+    /// Sends a signed request with `params` as an `application/x-www-form-urlencoded` body.
+    /// If `params` is empty, no body is sent.
+    pub fn send_form<N, V>(
+        &self,
+        method: Method,
+        url: &Url,
+        params: impl IntoIterator<Item = (N, V)>,
+    ) -> Result<String>
+    where
+        N: Into<String>,
+        V: Into<String>,
+    {
+        let form_params = params
+            .into_iter()
+            .map(|(name, value)| ParamPair::pair(name, value))
+            .collect::<Vec<_>>();
+        if let Some(pair) = form_params.iter().find(|p| p.name.starts_with("oauth_")) {
+            return Err(OagainError::DisallowedOauthParam(pair.name.clone()));
+        }
+
+        let body = if form_params.is_empty() {
+            RequestBody::Empty
+        } else {
+            RequestBody::Form(form_params)
+        };
+        self.send_request_body(method, url, body)
+    }
+
+    /// Sends a signed request with a body that is not form-encoded (e.g., JSON or XML).
+    /// Since the body is not included in the signature, it is protected with an
+    /// `oauth_body_hash` param instead, unless that was disabled in the builder.
+    pub fn send_body(
+        &self,
+        method: Method,
+        url: &Url,
+        content_type: impl Into<String>,
+        body: impl Into<Vec<u8>>,
+    ) -> Result<String> {
+        self.send_request_body(
+            method,
+            url,
+            RequestBody::Raw {
+                content_type: content_type.into(),
+                bytes: body.into(),
+            },
+        )
+    }
+
+    fn send_request_body(&self, method: Method, url: &Url, body: RequestBody) -> Result<String> {
         // TODO: add param processing.
+        self.send_signed(method, url, body, HeaderMap::new())?
+            .text()
+    }
 
-        Ok(response_str)
+    pub(crate) fn send_signed(
+        &self,
+        method: Method,
+        url: &Url,
+        body: RequestBody,
+        headers: HeaderMap,
+    ) -> Result<SignedResponse> {
+        let state = self.ensure_auth()?;
+
+        let response = self.canned_request(&state, method, url, &body, headers)?;
+        debug!("send_signed: response: {:?}", response);
+
+        Ok(response)
     }
 
-    pub fn get_fake(&mut self, url: &Url) -> Result<String> {
-        self.ensure_auth()?;
+    pub fn get_fake(&self, url: &Url) -> Result<String> {
+        let state = self.ensure_auth()?;
 
-        let auth_header = self.sign_request_from_components("GET", url)?;
-        println!("Method: '{}'", "GET");
-        println!("Url:    '{}'", url.to_string());
+        let auth_header =
+            self.sign_request_from_components(&state, "GET", url, &RequestBody::Empty)?;
+        println!("Method: 'GET'");
+        println!("Url:    '{}'", url);
         println!("Header: '{}'", auth_header);
         Ok("FAKE".to_string())
     }
 
-    pub fn retrieve_request_token(&mut self) -> Result<()> {
-        let response = self.canned_request("GET", &self.request_token_url.clone())?;
-        let response_str = String::from_utf8(Vec::from(response.bytes()?))?;
+    pub fn retrieve_request_token(&self) -> Result<()> {
+        self.retrieve_request_token_with_callback(self.config.callback_url.as_ref())
+    }
 
+    fn retrieve_request_token_with_callback(&self, callback_url: Option<&Url>) -> Result<()> {
         // TODO: check the incoming state.
-
-        let params = decode_params_string(response_str);
-        let mut request_token = None;
-        let mut token_secret = None;
-        for param in params {
-            let name = param.name;
-            if name == "oauth_token" {
-                // TODO: Do you want to check for None?
-                request_token = param.value;
-            } else if name == "oauth_token_secret" {
-                // TODO: Do you want to check for None?
-                token_secret = param.value;
-            }
-        }
-        // TODO: At this point, you should check to make sure that both params were present.
-        self.state = ConsumerState::RequestToken {
+        let (request_token, token_secret) = self.config.retrieve_token_pair(
+            &self.state(),
+            callback_url,
+            &self.config.request_token_url,
+        )?;
+        self.session().state = ConsumerState::RequestToken {
             request_token: request_token.ok_or(OagainError::MissingRequestToken)?,
             token_secret: token_secret.ok_or(OagainError::MissingTokenSecret)?,
         };
@@ -135,14 +408,15 @@ impl<NP: NonceProvider> Consumer<NP> {
         Ok(())
     }
 
-    pub fn set_verification_code(&mut self, code: impl AsRef<str>) -> Result<()> {
-        self.state = ConsumerState::UserAuth {
-            request_token: self
+    pub fn set_verification_code(&self, code: impl AsRef<str>) -> Result<()> {
+        let mut session = self.session();
+        session.state = ConsumerState::UserAuth {
+            request_token: session
                 .state
                 .token()
                 .ok_or(OagainError::MissingRequestToken)?
                 .to_string(),
-            token_secret: self
+            token_secret: session
                 .state
                 .token_secret()
                 .ok_or(OagainError::MissingTokenSecret)?
@@ -152,213 +426,319 @@ impl<NP: NonceProvider> Consumer<NP> {
         Ok(())
     }
 
-    fn toml_now() -> toml::value::Datetime {
-        let utc = Utc::now();
-        toml::value::Datetime {
-            date: Some(toml::value::Date {
-                year: utc.year() as u16,
-                month: utc.month() as u8,
-                day: utc.day() as u8,
-            }),
-            time: Some(toml::value::Time {
-                hour: utc.hour() as u8,
-                minute: utc.minute() as u8,
-                second: utc.second() as u8,
-                nanosecond: 0,
-            }),
-            offset: Some(toml::value::Offset::Z),
+    fn save_token(&self) -> Result<()> {
+        self.save_token_if(|_| true)
+    }
+
+    // Saves the token if `is_due` approves, given the last-use time already in the store.
+    fn save_token_if(&self, is_due: impl Fn(Option<DateTime<Utc>>) -> bool) -> Result<()> {
+        if let Some(store) = &self.token_store {
+            let mut saved_last_use = self
+                .saved_last_use
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            if !is_due(*saved_last_use) {
+                return Ok(());
+            }
+            let token = {
+                let session = self.session();
+                let times = session.token_times.unwrap_or(TokenTimes::new(Utc::now()));
+                SavedToken {
+                    access_token: session.state.token().unwrap_or_default().to_string(),
+                    token_secret: session.state.token_secret().unwrap_or_default().to_string(),
+                    issued: times.issued,
+                    last_used: times.last_used,
+                }
+            };
+            store.save(&token)?;
+            *saved_last_use = Some(token.last_used);
         }
+        Ok(())
     }
 
-    fn write_state_to_save_file(&self) -> Result<()> {
-        if let Some(save_file) = &self.save_file {
-            let mut table = toml::Table::new();
-            table.insert(
-                ACCESS_TOKEN_NAME.to_string(),
-                Value::String(self.state.token().unwrap_or_default().to_string()),
-            );
-            table.insert(
-                TOKEN_SECRET_NAME.to_string(),
-                Value::String(self.state.token_secret().unwrap_or_default().to_string()),
-            );
-            table.insert(
-                TOKEN_SAVE_TIME.to_string(),
-                Value::Datetime(Self::toml_now()),
-            );
-
-            let mut f = File::create(save_file)?;
-            f.write_all(table.to_string().as_bytes())?;
+    fn clear_saved_token(&self) -> Result<()> {
+        if let Some(store) = &self.token_store {
+            store.clear()?;
         }
         Ok(())
     }
 
-    pub fn retrieve_access_token(&mut self) -> Result<()> {
-        debug!("retrieve_access_token: {:?}", self);
-        let response = self.canned_request("GET", &self.access_token_url.clone())?;
-        debug!("access raw response: {:?}", response);
-        let response_str: String = String::from_utf8(Vec::from(response.bytes()?))?;
-        debug!("access response: {}", response_str);
-
-        let params = decode_params_string(response_str);
-        let mut access_token = None;
-        let mut token_secret = None;
-        for param in params {
-            if param.name == OAUTH_TOKEN_PARAM_NAME {
-                access_token = param.value;
-            } else if param.name == OAUTH_TOKEN_SECRET_PARAM_NAME {
-                token_secret = param.value;
+    /// Waits up to `timeout` for the provider to redirect the user to `server`, then uses the
+    /// verifier from the redirect. The callback URL must be `server.url()`.
+    pub fn receive_verification_code(
+        &self,
+        server: &CallbackServer,
+        timeout: Duration,
+    ) -> Result<()> {
+        let request_token = self
+            .state()
+            .token()
+            .ok_or(OagainError::MissingRequestToken)?
+            .to_string();
+        let verifier = server.wait_for_verifier(&request_token, timeout)?;
+        self.set_verification_code(verifier)
+    }
+
+    /// The request token waiting for the user to authorize it, if there is one.
+    pub fn pending_authorization(&self) -> Option<PendingAuthorization> {
+        match &self.session().state {
+            ConsumerState::RequestToken {
+                request_token,
+                token_secret,
             }
+            | ConsumerState::UserAuth {
+                request_token,
+                token_secret,
+                ..
+            } => Some(PendingAuthorization {
+                request_token: request_token.clone(),
+                token_secret: token_secret.clone(),
+            }),
+            _ => None,
         }
+    }
 
-        self.state = ConsumerState::FullAuth {
-            access_token: access_token.ok_or(OagainError::MissingAccessToken)?,
-            token_secret: token_secret.ok_or(OagainError::MissingTokenSecret)?,
+    /// Finishes an authorization started elsewhere: uses the `verifier` the user received for
+    /// `pending`, and retrieves the access token.
+    pub fn resume_authorization(
+        &self,
+        pending: PendingAuthorization,
+        verifier: impl AsRef<str>,
+    ) -> Result<()> {
+        self.restart(pending.into());
+        self.set_verification_code(verifier)?;
+        self.retrieve_access_token()
+    }
+
+    pub fn retrieve_access_token(&self) -> Result<()> {
+        debug!("retrieve_access_token: {:?}", self);
+        let (access_token, token_secret) =
+            self.config
+                .retrieve_token_pair(&self.state(), None, &self.config.access_token_url)?;
+
+        *self.session() = Session {
+            state: ConsumerState::FullAuth {
+                access_token: access_token.ok_or(OagainError::MissingAccessToken)?,
+                token_secret: token_secret.ok_or(OagainError::MissingTokenSecret)?,
+            },
+            token_times: Some(TokenTimes::new(Utc::now())),
         };
 
-        if let Err(err) = self.write_state_to_save_file() {
-            error!("Failed writing to save file: {}", err.to_string())
+        if let Err(err) = self.save_token() {
+            error!(
+                "Failed writing to save file{}: {}",
+                profile_label(&self.profile),
+                err
+            )
         }
 
         Ok(())
     }
 
-    pub fn make_user_auth_url(&mut self) -> Result<Url> {
-        let mut base_url = self.user_authorization_url.clone();
-        let request_token = self.state.token().ok_or(OagainError::MissingRequestToken)?;
-        base_url
-            .query_pairs_mut()
-            .append_pair(&self.user_auth_token_param_name, request_token)
-            .append_pair(&self.user_auth_key_param_name, &self.consumer_key);
-        Ok(base_url)
-    }
-
-    fn canned_request(&mut self, method: impl AsRef<str>, url: &Url) -> Result<Response> {
-        //let url = url(self).clone();
-        let auth_header = self.sign_request_from_components(method, url)?;
-        debug!("auth_header: {}", auth_header);
-        // TODO: reuse these clients.
-        let client = Client::builder().build()?;
-        // TODO: make this a fold()
-        let response = client
-            .get(url.clone())
-            .header("Authorization", auth_header)
-            .send()?;
+    /// Reactivates an access token that has gone unused for too long, and records the renewal
+    /// in the save file. This is called automatically when the saved token needs it.
+    pub fn renew_access_token(&self) -> Result<()> {
+        let url = self
+            .config
+            .renew_access_token_url
+            .clone()
+            .ok_or(OagainError::MissingRenewAccessTokenUrl)?;
+        let state = self.full_auth().ok_or(OagainError::MissingAccessToken)?;
+        if let Some(times) = self.access_token_times() {
+            let status =
+                self.config
+                    .token_lifetime
+                    .status(times.issued, times.last_used, Utc::now());
+            if status == TokenStatus::Expired {
+                return Err(OagainError::OldToken);
+            }
+        }
+        // Sending the request records the use, which is what renewal is for.
+        let response = self.canned_request(
+            &state,
+            Method::GET,
+            &url,
+            &RequestBody::Empty,
+            HeaderMap::new(),
+        )?;
+        debug!("renew response: {:?}", response);
+
+        Ok(())
+    }
 
+    /// Revokes the access token, so that it can't be used again, then forgets it and scrubs the
+    /// save file. The local session is ended even if the server refuses the revocation.
+    pub fn revoke(&self) -> Result<()> {
+        let url = self
+            .config
+            .revoke_access_token_url
+            .clone()
+            .ok_or(OagainError::MissingRevokeAccessTokenUrl)?;
+        let result = match self.full_auth() {
+            Some(state) => self
+                .canned_request(
+                    &state,
+                    Method::GET,
+                    &url,
+                    &RequestBody::Empty,
+                    HeaderMap::new(),
+                )
+                .map(|response| debug!("revoke response: {:?}", response)),
+            None => Ok(()),
+        };
+
+        self.forget_access_token();
+        self.clear_saved_token()?;
+
+        result
+    }
+
+    pub fn make_user_auth_url(&self) -> Result<Url> {
+        let pending = self
+            .pending_authorization()
+            .ok_or(OagainError::MissingRequestToken)?;
+        Ok(self.config.user_auth_url(&pending))
+    }
+
+    fn canned_request(
+        &self,
+        state: &ConsumerState,
+        method: Method,
+        url: &Url,
+        body: &RequestBody,
+        headers: HeaderMap,
+    ) -> Result<SignedResponse> {
+        let response = self
+            .config
+            .send_request(state, None, method, url, body, headers)?;
+
+        self.record_token_use();
         Ok(response)
     }
 
+    // Saves the last-use time of the access token, for idle timeouts.
+    fn record_token_use(&self) {
+        {
+            let mut session = self.session();
+            if !matches!(session.state, ConsumerState::FullAuth { .. }) {
+                return;
+            }
+            let now = Utc::now();
+            let times = session.token_times.get_or_insert(TokenTimes::new(now));
+            // Threads may get here out of order, and the time must never go backwards.
+            times.last_used = times.last_used.max(now);
+        }
+        let step = chrono::Duration::minutes(LAST_USE_SAVE_MINUTES);
+        let result = self.save_token_if(|saved_last_use| {
+            let Some(times) = self.access_token_times() else {
+                return false;
+            };
+            saved_last_use.is_none_or(|saved| times.last_used - saved >= step)
+        });
+        if let Err(err) = result {
+            error!(
+                "Failed writing to save file{}: {}",
+                profile_label(&self.profile),
+                err
+            )
+        }
+    }
+
+    /// Returns the Authorization header for the request, signed with the tokens in `state`.
     fn sign_request_from_components(
-        &mut self,
+        &self,
+        state: &ConsumerState,
         method: impl AsRef<str>,
         url: &Url,
+        body: &RequestBody,
     ) -> Result<String> {
-        let (timestamp, nonce) = self.nonce()?;
-        debug!("timestamp, nonce: {}, {}", timestamp, nonce);
-        let standard_params = self.oauth_param_list(timestamp, nonce);
-        debug!("standard_params: {:?}", standard_params);
-
-        let other_params = url
-            .query_pairs()
-            .map(|(name, value)| ParamPair::pair(name, value))
-            .collect::<Vec<_>>();
-
-        let param_iter = standard_params.iter().chain(other_params.iter());
+        self.config
+            .sign_request_from_components(state, None, method, url, body)
+    }
 
-        let string_to_sign = concat_request_elements(method.as_ref(), url, param_iter.cloned());
-        debug!("string_to_sign: {}", string_to_sign);
+    /// The access token, if the consumer has one.
+    pub fn access_token(&self) -> Option<AccessToken> {
+        match &self.session().state {
+            ConsumerState::FullAuth {
+                access_token,
+                token_secret,
+            } => Some(AccessToken {
+                token: access_token.clone(),
+                secret: token_secret.clone(),
+            }),
+            _ => None,
+        }
+    }
 
-        let signing_key = make_signing_key(
-            &self.consumer_secret,
-            self.state.token_secret().unwrap_or_default(),
-        );
-        debug!("signing_key: {}", signing_key);
-        let signature = sign_string_hmac(signing_key, string_to_sign);
+    /// The settings that don't depend on the user.
+    pub fn config(&self) -> &ConsumerConfig<NP> {
+        &self.config
+    }
 
-        let header = self.oauth_header(&standard_params, signature);
-        Ok(header)
+    /// Drops this user's tokens, keeping the settings for signing requests for any user.
+    pub fn into_config(self) -> ConsumerConfig<NP> {
+        self.config
     }
 
+    //----------------------------------------------------------------------
+
+    #[cfg(test)]
     pub(crate) fn oauth_param_list(
         &self,
         timestamp: u32,
         nonce: impl AsRef<str>,
     ) -> Vec<ParamPair> {
-        let (include_callback, include_token, include_verifier) = match &self.state {
-            ConsumerState::NoAuth => (true, false, false),
-            ConsumerState::RequestToken { .. } => (false, true, false),
-            ConsumerState::UserAuth { .. } => (false, true, true),
-            ConsumerState::FullAuth { .. } => (false, true, false),
-        };
-        let pair_descriptions: &[(&'static str, &dyn Fn() -> Option<String>)] = &[
-            (OAUTH_CONSUMER_KEY_PARAM_NAME, &|| {
-                Some(self.consumer_key.clone())
-            }),
-            (OAUTH_SIGNATURE_METHOD_PARAM_NAME, &|| {
-                Some(OAUTH_SIGNATURE_METHOD_HMAC_VALUE.to_string())
-            }),
-            (OAUTH_TIMESTAMP_PARAM_NAME, &|| Some(timestamp.to_string())),
-            (OAUTH_NONCE_PARAM_NAME, &|| {
-                nonce.as_ref().to_string().into()
-            }),
-            (OAUTH_VERSION_PARAM_NAME, &|| {
-                OAUTH_VERSION_VALUE.to_string().into()
-            }),
-            (OAUTH_CALLBACK_PARAM_NAME, &|| {
-                include_callback.option(OAUTH_CALLBACK_OOB_VALUE.to_string())
-            }),
-            (OAUTH_TOKEN_PARAM_NAME, &|| {
-                // TODO: bad unwrap
-                // TODO: need to use access_token sometimes.
-                include_token.option_with(|| self.state.token().unwrap().to_string())
-            }),
-            (OAUTH_VERIFIER_PARAM_NAME, &|| {
-                // TODO: bad unwrap
-                include_verifier.option_with(|| self.state.verification_code().unwrap().to_string())
-            }),
-        ];
-
-        pair_descriptions
-            .iter()
-            .fold(Vec::default(), |mut acc, (name, value_func)| {
-                if let Some(val) = value_func() {
-                    acc.push(ParamPair::pair(*name, val));
-                }
-                acc
-            })
+        self.config.oauth_param_list(
+            &self.state(),
+            self.config.callback_url.as_ref(),
+            timestamp,
+            nonce,
+        )
     }
 
-    //----------------------------------------------------------------------
-
-    pub(crate) fn nonce(&mut self) -> Result<(u32, String)> {
-        self.nonce_provider.nonce()
+    #[cfg(test)]
+    pub(crate) fn nonce(&self) -> Result<(u32, String)> {
+        self.config.nonce()
     }
 
+    #[cfg(test)]
     pub(crate) fn oauth_header(
         &self,
         param_pairs: &[ParamPair],
         signature: impl AsRef<str>,
     ) -> String {
-        let signature_pair = ParamPair::pair(OAUTH_SIGNATURE_PARAM_NAME, signature.as_ref());
-        format!(
-            "OAuth {}",
-            param_pairs
-                .iter()
-                .sorted()
-                .chain(once(&signature_pair))
-                .map(|pp| pp.to_wrapped_string())
-                .join(", ")
-        )
+        self.config.oauth_header(param_pairs, signature)
     }
 }
 
 #[cfg(test)]
 pub(crate) trait ConsumerTestFuncs {
     fn request_url(&self) -> &Url;
+    fn consumer_secret(&self) -> &str;
+    fn signature_method(&self) -> &dyn SignatureMethod;
+    fn sign_request(&self, method: &str, url: &Url, form_params: &[ParamPair]) -> Result<String>;
 }
 
 #[cfg(test)]
 impl<NP: NonceProvider> ConsumerTestFuncs for Consumer<NP> {
     fn request_url(&self) -> &Url {
-        &self.request_token_url
+        &self.config.request_token_url
+    }
+
+    fn consumer_secret(&self) -> &str {
+        &self.config.consumer_secret
+    }
+
+    fn signature_method(&self) -> &dyn SignatureMethod {
+        self.config.signature_method.as_ref()
+    }
+
+    fn sign_request(&self, method: &str, url: &Url, form_params: &[ParamPair]) -> Result<String> {
+        self.sign_request_from_components(
+            &self.state(),
+            method,
+            url,
+            &RequestBody::Form(form_params.to_vec()),
+        )
     }
 }
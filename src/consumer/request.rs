@@ -0,0 +1,134 @@
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION};
+use reqwest::Method;
+use serde::Serialize;
+use url::Url;
+
+use crate::consumer::request_body::RequestBody;
+use crate::consumer::response::SignedResponse;
+use crate::consumer::Consumer;
+use crate::error::{OagainError, Result};
+use crate::nonce_provider::NonceProvider;
+use crate::parameters::ParamPair;
+
+/// A builder for a signed request, created by `Consumer::request()`.
+///
+/// Errors from the builder methods are held until `send()` is called.
+#[derive(Debug)]
+pub struct SignedRequestBuilder<'a, NP: NonceProvider> {
+    consumer: &'a Consumer<NP>,
+    method: Method,
+    url: Url,
+    headers: HeaderMap,
+    body: RequestBody,
+    error: Option<OagainError>,
+}
+
+impl<'a, NP: NonceProvider> SignedRequestBuilder<'a, NP> {
+    pub(crate) fn new(
+        consumer: &'a Consumer<NP>,
+        method: Method,
+        url: &Url,
+    ) -> SignedRequestBuilder<'a, NP> {
+        SignedRequestBuilder {
+            consumer,
+            method,
+            url: url.clone(),
+            headers: HeaderMap::new(),
+            body: RequestBody::Empty,
+            error: None,
+        }
+    }
+
+    /// Appends `pairs` to the URL's query. Query params are included in the signature.
+    pub fn query<N, V>(mut self, pairs: impl IntoIterator<Item = (N, V)>) -> Self
+    where
+        N: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let mut pairs = pairs.into_iter().peekable();
+        if pairs.peek().is_some() {
+            self.url.query_pairs_mut().extend_pairs(pairs);
+        }
+        self
+    }
+
+    /// Sends `pairs` as an `application/x-www-form-urlencoded` body. The params are included in
+    /// the signature. Repeated calls add to the form.
+    pub fn form<N, V>(mut self, pairs: impl IntoIterator<Item = (N, V)>) -> Self
+    where
+        N: Into<String>,
+        V: Into<String>,
+    {
+        let mut params = match std::mem::take(&mut self.body) {
+            RequestBody::Form(params) => params,
+            _ => vec![],
+        };
+        for (name, value) in pairs {
+            let pair = ParamPair::pair(name, value);
+            if pair.name.starts_with("oauth_") {
+                self.set_error(OagainError::DisallowedOauthParam(pair.name.clone()));
+            }
+            params.push(pair);
+        }
+        self.body = RequestBody::Form(params);
+        self
+    }
+
+    /// Sends `value` serialized as an `application/json` body.
+    pub fn json<T: Serialize + ?Sized>(mut self, value: &T) -> Self {
+        match serde_json::to_vec(value) {
+            Ok(bytes) => {
+                self.body = RequestBody::Raw {
+                    content_type: "application/json".to_string(),
+                    bytes,
+                }
+            }
+            Err(err) => self.set_error(err.into()),
+        }
+        self
+    }
+
+    /// Sends `bytes` as the body. The content type is `application/octet-stream`, unless it is
+    /// set with `header()`.
+    pub fn body(mut self, bytes: impl Into<Vec<u8>>) -> Self {
+        self.body = RequestBody::Raw {
+            content_type: "application/octet-stream".to_string(),
+            bytes: bytes.into(),
+        };
+        self
+    }
+
+    /// Adds a header to the request. The `Authorization` header is reserved for the signature.
+    pub fn header(mut self, name: impl AsRef<str>, value: impl AsRef<str>) -> Self {
+        let name = name.as_ref();
+        match (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value.as_ref()),
+        ) {
+            (Ok(name), _) if name == AUTHORIZATION => {
+                self.set_error(OagainError::BadHeader(name.to_string()))
+            }
+            (Ok(name), Ok(value)) => {
+                self.headers.append(name, value);
+            }
+            _ => self.set_error(OagainError::BadHeader(name.to_string())),
+        }
+        self
+    }
+
+    /// Signs and sends the request. Non-2xx responses are returned as `OagainError::HttpStatus`.
+    pub fn send(self) -> Result<SignedResponse> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        self.consumer
+            .send_signed(self.method, &self.url, self.body, self.headers)
+    }
+
+    fn set_error(&mut self, err: OagainError) {
+        // Keep the first error, since later ones may just be fallout from it.
+        if self.error.is_none() {
+            self.error = Some(err);
+        }
+    }
+}
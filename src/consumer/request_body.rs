@@ -0,0 +1,45 @@
+use itertools::Itertools;
+
+use crate::parameters::ParamPair;
+
+/// The body of a request, which determines how it is included in the signature.
+#[derive(Debug, Default, Clone)]
+pub(crate) enum RequestBody {
+    /// No body at all.
+    #[default]
+    Empty,
+
+    /// An `application/x-www-form-urlencoded` body. The params are signed directly. (Spec 9.1.1)
+    Form(Vec<ParamPair>),
+
+    /// Any other body. It is covered by `oauth_body_hash`, if enabled.
+    Raw {
+        content_type: String,
+        bytes: Vec<u8>,
+    },
+}
+
+impl RequestBody {
+    pub fn form_params(&self) -> &[ParamPair] {
+        match self {
+            RequestBody::Form(params) => params,
+            _ => &[],
+        }
+    }
+
+    pub fn content_type(&self) -> Option<&str> {
+        match self {
+            RequestBody::Empty => None,
+            RequestBody::Form(_) => Some("application/x-www-form-urlencoded"),
+            RequestBody::Raw { content_type, .. } => Some(content_type),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Option<Vec<u8>> {
+        match self {
+            RequestBody::Empty => None,
+            RequestBody::Form(params) => Some(params.iter().join("&").into_bytes()),
+            RequestBody::Raw { bytes, .. } => Some(bytes.clone()),
+        }
+    }
+}
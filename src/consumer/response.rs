@@ -0,0 +1,70 @@
+use reqwest::blocking::Response;
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+
+use crate::error::{OagainError, Result};
+use crate::problem::ProblemReport;
+
+/// The response to a signed request. The body has already been read in full.
+#[derive(Debug, Clone)]
+pub struct SignedResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    bytes: Vec<u8>,
+}
+
+impl SignedResponse {
+    pub(crate) fn read(response: Response) -> Result<SignedResponse> {
+        Ok(SignedResponse {
+            status: response.status(),
+            headers: response.headers().clone(),
+            bytes: Vec::from(response.bytes()?),
+        })
+    }
+
+    /// Turns a non-2xx response into an `OagainError::OauthProblem` if the provider reported
+    /// one, or an `OagainError::HttpStatus` otherwise.
+    pub(crate) fn error_for_status(self) -> Result<SignedResponse> {
+        if self.status.is_success() {
+            Ok(self)
+        } else if let Some(report) = ProblemReport::from_response(&self.headers, &self.bytes) {
+            Err(OagainError::OauthProblem {
+                status: self.status,
+                report: Box::new(report),
+            })
+        } else {
+            Err(OagainError::HttpStatus {
+                status: self.status,
+                headers: Box::new(self.headers),
+                body: String::from_utf8_lossy(&self.bytes).into_owned(),
+            })
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    /// The body as a UTF-8 string.
+    pub fn text(&self) -> Result<String> {
+        Ok(String::from_utf8(self.bytes.clone())?)
+    }
+
+    /// The body deserialized from JSON.
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T> {
+        Ok(serde_json::from_slice(&self.bytes)?)
+    }
+}
@@ -1,4 +1,6 @@
-#[derive(Debug, Default)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Clone)]
 pub enum ConsumerState {
     #[default]
     /// Initial state. Ready to retrieve a request token from the server.
@@ -52,3 +54,21 @@ impl ConsumerState {
         }
     }
 }
+
+/// A request token waiting for the user to authorize it, which can be kept between the HTTP
+/// requests (or processes) that start and finish authorization. The token secret is in here, so
+/// keep it server-side, e.g., in the session store, rather than in a cookie.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingAuthorization {
+    pub request_token: String,
+    pub token_secret: String,
+}
+
+impl From<PendingAuthorization> for ConsumerState {
+    fn from(pending: PendingAuthorization) -> Self {
+        ConsumerState::RequestToken {
+            request_token: pending.request_token,
+            token_secret: pending.token_secret,
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use url::Url;
+
+use crate::consumer::test_cases::{access_token_response, build, unauthed_builder};
+use crate::consumer::{Headless, LoopbackCallback, UserAuthorizer};
+use crate::error::{OagainError, Result};
+use crate::test_server::{CannedResponse, TestServer};
+
+// Answers like a user who authorized, and remembers the URL and request token it was given.
+#[derive(Debug, Default, Clone)]
+struct ScriptedUser {
+    asked: Arc<Mutex<Option<(Url, String)>>>,
+}
+
+impl UserAuthorizer for ScriptedUser {
+    fn authorize(&mut self, url: &Url, request_token: &str) -> Result<String> {
+        *self.asked.lock().unwrap() = Some((url.clone(), request_token.to_string()));
+        Ok("hfdp7dh39dks9884".to_string())
+    }
+}
+
+fn token_responses() -> Vec<CannedResponse> {
+    vec![
+        CannedResponse::ok("oauth_token=hh5s93j4hdidpola&oauth_token_secret=hdhd0244k9j7ao03"),
+        access_token_response(),
+    ]
+}
+
+#[test]
+fn authorizer_is_used() {
+    let mut responses = token_responses();
+    responses.push(CannedResponse::ok("photos"));
+    let server = TestServer::start(responses);
+    let user = ScriptedUser::default();
+    let consumer = build(unauthed_builder(&server).set_user_authorizer(user.clone()));
+
+    assert_eq!("photos", consumer.get(&server.url("/photos")).unwrap());
+    let (url, request_token) = user.asked.lock().unwrap().clone().unwrap();
+    assert_eq!("hh5s93j4hdidpola", request_token);
+    assert!(url.as_str().starts_with(server.url("/authorize").as_str()));
+    assert_eq!(
+        "hfdp7dh39dks9884",
+        server.requests()[1]
+            .oauth_params()
+            .into_iter()
+            .find(|p| p.name == "oauth_verifier")
+            .and_then(|p| p.value)
+            .unwrap()
+    );
+}
+
+#[test]
+fn headless_needs_user_auth() {
+    let server = TestServer::start(token_responses());
+    let consumer = build(unauthed_builder(&server).set_user_authorizer(Headless));
+
+    let Err(OagainError::NeedsUserAuth(url)) = consumer.get(&server.url("/photos")) else {
+        panic!("expected NeedsUserAuth");
+    };
+    assert!(url
+        .query_pairs()
+        .any(|(name, value)| name == "oauth_token" && value == "hh5s93j4hdidpola"));
+
+    // The user authorizes elsewhere, and the service finishes up.
+    consumer.set_verification_code("hfdp7dh39dks9884").unwrap();
+    consumer.retrieve_access_token().unwrap();
+    assert!(consumer.is_fully_authed());
+}
+
+// Finds a free port, so the simulated browser knows where to go.
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+// Follows the provider's redirect to the loopback callback on `port`, retrying until the consumer
+// is listening.
+fn follow_redirect(port: u16) -> JoinHandle<StatusCode> {
+    let redirect = Url::parse(&format!(
+        "http://127.0.0.1:{}/callback?oauth_token=hh5s93j4hdidpola&oauth_verifier=hfdp7dh39dks9884",
+        port
+    ))
+    .unwrap();
+    std::thread::spawn(move || loop {
+        if let Ok(response) = reqwest::blocking::get(redirect.clone()) {
+            return response.status();
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    })
+}
+
+fn loopback_callback(port: u16) -> LoopbackCallback {
+    LoopbackCallback::new(port)
+        .open_browser(false)
+        .set_timeout(Duration::from_secs(5))
+}
+
+#[test]
+fn loopback_callback_is_used() {
+    let port = free_port();
+    let mut responses = token_responses();
+    responses.push(CannedResponse::ok("photos"));
+    let server = TestServer::start(responses);
+    let consumer = build(unauthed_builder(&server).set_user_authorizer(loopback_callback(port)));
+
+    let browser = follow_redirect(port);
+    assert_eq!("photos", consumer.get(&server.url("/photos")).unwrap());
+    assert!(browser.join().unwrap().is_success());
+}
+
+#[test]
+fn loopback_callback_retries_on_same_port() {
+    let port = free_port();
+    let mut responses = vec![CannedResponse::new(503, "try again later")];
+    responses.extend(token_responses());
+    responses.push(CannedResponse::ok("photos"));
+    let server = TestServer::start(responses);
+    let consumer = build(unauthed_builder(&server).set_user_authorizer(loopback_callback(port)));
+
+    // The request token can't be retrieved, so the server is started but never waited on.
+    assert!(consumer.get(&server.url("/photos")).is_err());
+
+    let browser = follow_redirect(port);
+    assert_eq!("photos", consumer.get(&server.url("/photos")).unwrap());
+    assert!(browser.join().unwrap().is_success());
+}
@@ -0,0 +1,96 @@
+use reqwest::Method;
+use sha1::Sha1;
+
+use crate::consumer::test_cases::{authed_builder, authed_consumer, build, signature_is_valid};
+use crate::parameters::ParamPair;
+use crate::signing::hash_body;
+use crate::test_server::{CannedResponse, TestServer};
+
+const ORDER_JSON: &str = r#"{"PlaceOrderRequest":{"orderType":"EQ","clientOrderId":"88"}}"#;
+
+fn body_hash_param(params: &[ParamPair]) -> Option<&str> {
+    params
+        .iter()
+        .find(|p| p.name == "oauth_body_hash")
+        .and_then(|p| p.value.as_deref())
+}
+
+#[test]
+fn spec_example() {
+    // From the OAuth Request Body Hash draft, Appendix A.
+    assert_eq!(
+        "Lve95gjOVATpfV8EL5X4nxwjKHE=",
+        hash_body::<Sha1>(b"Hello World!")
+    );
+}
+
+#[test]
+fn json_body_is_hashed() {
+    let server = TestServer::start(vec![CannedResponse::ok("placed")]);
+    let consumer = authed_consumer(&server);
+
+    let url = server.url("/v1/accounts/xyz/orders/place");
+    let response = consumer
+        .send_body(Method::PUT, &url, "application/json", ORDER_JSON)
+        .unwrap();
+    assert_eq!("placed", response);
+
+    let requests = server.requests();
+    let request = &requests[0];
+    assert_eq!("PUT", request.method);
+    assert_eq!(Some("application/json"), request.header("content-type"));
+    assert_eq!(ORDER_JSON.as_bytes(), request.body);
+
+    let oauth_params = request.oauth_params();
+    assert_eq!(
+        Some(hash_body::<Sha1>(ORDER_JSON.as_bytes()).as_str()),
+        body_hash_param(&oauth_params)
+    );
+    // The body itself is not in the base string; only the hash is.
+    assert!(signature_is_valid(request, &[]));
+}
+
+#[test]
+fn body_hash_can_be_disabled() {
+    let server = TestServer::start(vec![CannedResponse::ok("placed")]);
+    let consumer = build(authed_builder(&server).use_body_hash(false));
+
+    let url = server.url("/orders");
+    consumer
+        .send_body(Method::POST, &url, "application/xml", "<order/>")
+        .unwrap();
+
+    let requests = server.requests();
+    assert_eq!(None, body_hash_param(&requests[0].oauth_params()));
+    assert!(signature_is_valid(&requests[0], &[]));
+}
+
+#[test]
+fn no_body_hash_for_forms() {
+    let server = TestServer::start(vec![CannedResponse::ok("posted")]);
+    let consumer = authed_consumer(&server);
+
+    consumer
+        .post_form(&server.url("/orders"), [("symbol", "IBM")])
+        .unwrap();
+
+    let requests = server.requests();
+    assert_eq!(None, body_hash_param(&requests[0].oauth_params()));
+}
+
+#[test]
+fn no_body_hash_for_plaintext() {
+    let server = TestServer::start(vec![CannedResponse::ok("placed")]);
+    let consumer = build(
+        authed_builder(&server)
+            .use_plaintext()
+            .allow_insecure_plaintext(true),
+    );
+
+    consumer
+        .send_body(Method::POST, &server.url("/orders"), "text/plain", "hi")
+        .unwrap();
+
+    let requests = server.requests();
+    assert_eq!(None, body_hash_param(&requests[0].oauth_params()));
+}
@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+use crate::consumer::test_cases::{access_token_response, build, unauthed_builder};
+use crate::consumer::CallbackServer;
+use crate::test_server::{CannedResponse, TestServer};
+
+#[test]
+fn verifier_from_callback_server() {
+    let server = TestServer::start(vec![
+        CannedResponse::ok("oauth_token=hh5s93j4hdidpola&oauth_token_secret=hdhd0244k9j7ao03"),
+        access_token_response(),
+    ]);
+    let callback_server = CallbackServer::bind(0).unwrap();
+    let consumer = build(
+        unauthed_builder(&server)
+            .set_callback_url(callback_server.url().clone())
+            .unwrap(),
+    );
+
+    consumer.retrieve_request_token().unwrap();
+    // The provider redirects the browser once the user authorizes.
+    let redirect = callback_server
+        .url()
+        .join("?oauth_token=hh5s93j4hdidpola&oauth_verifier=hfdp7dh39dks9884")
+        .unwrap();
+    let browser = std::thread::spawn(move || reqwest::blocking::get(redirect).unwrap().status());
+    consumer
+        .receive_verification_code(&callback_server, Duration::from_secs(5))
+        .unwrap();
+    assert!(browser.join().unwrap().is_success());
+    consumer.retrieve_access_token().unwrap();
+    assert!(consumer.is_fully_authed());
+
+    let requests = server.requests();
+    let param = |index: usize, name: &str| {
+        requests[index]
+            .oauth_params()
+            .into_iter()
+            .find(|p| p.name == name)
+            .and_then(|p| p.value)
+    };
+    assert_eq!(
+        Some(callback_server.url().to_string()),
+        param(0, "oauth_callback")
+    );
+    assert_eq!(
+        Some("hfdp7dh39dks9884".to_string()),
+        param(1, "oauth_verifier")
+    );
+}
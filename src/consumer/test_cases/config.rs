@@ -0,0 +1,122 @@
+use std::fs;
+
+use chrono::Duration;
+use tempfile::TempDir;
+
+use crate::consumer::test_cases::{build, write_save_file};
+use crate::consumer::Builder;
+use crate::error::OagainError;
+use crate::test_server::{CannedResponse, TestServer};
+
+fn write_config(dir: &TempDir, server: &TestServer, extra: &str) {
+    let config = format!(
+        r#"
+request_token_url = "{}"
+user_authorization_url = "{}"
+access_token_url = "{}"
+user_auth_key_param_name = "key"
+callback_url = "http://127.0.0.1:8080/callback"
+secrets_file = "secrets.toml"
+{}"#,
+        server.url("/request_token"),
+        server.url("/authorize"),
+        server.url("/access_token"),
+        extra
+    );
+    fs::write(dir.path().join("oagain.toml"), config).unwrap();
+    fs::write(
+        dir.path().join("secrets.toml"),
+        "token = \"dpf43f3p2l4k3l03\"\nsecret = \"kd94hf93k423kf44\"\n",
+    )
+    .unwrap();
+}
+
+#[test]
+fn consumer_from_config_file() {
+    let dir = TempDir::new().unwrap();
+    let server = TestServer::start(vec![CannedResponse::ok(
+        "oauth_token=hh5s93j4hdidpola&oauth_token_secret=hdhd0244k9j7ao03",
+    )]);
+    write_config(
+        &dir,
+        &server,
+        r#"
+[signature]
+method = "HMAC-SHA256"
+
+[token_lifetime]
+never_expires = true
+
+[store]
+kind = "toml"
+path = "save.toml"
+"#,
+    );
+    // Too old for the default policy, but these tokens never expire.
+    write_save_file(&dir.path().join("save.toml"), Duration::hours(30));
+
+    let consumer = build(Builder::from_config_file(dir.path().join("oagain.toml")).unwrap());
+    assert_eq!("dpf43f3p2l4k3l03", consumer.config.consumer_key);
+    assert_eq!("key", consumer.config.user_auth_key_param_name);
+    assert!(consumer.is_fully_authed());
+
+    consumer.forget_access_token();
+    consumer.retrieve_request_token().unwrap();
+    let params = server.requests()[0].oauth_params();
+    let param = |name: &str| {
+        params
+            .iter()
+            .find(|p| p.name == name)
+            .and_then(|p| p.value.clone())
+            .unwrap()
+    };
+    assert_eq!("HMAC-SHA256", param("oauth_signature_method"));
+    assert_eq!("http://127.0.0.1:8080/callback", param("oauth_callback"));
+}
+
+#[test]
+fn bad_config_files() {
+    let server = TestServer::start(vec![]);
+    for extra in [
+        "[signature]\nmethod = \"HMAC-MD5\"\n",
+        "[token_lifetime]\ndaily_cutoff = \"00:00\"\n",
+        "[token_lifetime]\ndaily_cutoff = \"00:00\"\ntimezone = \"Mars/Olympus\"\n",
+        "[token_lifetime]\n",
+        "[token_lifetime]\nnever_expires = false\n",
+        "[token_lifetime]\nnever_expires = true\nmax_age_minutes = 60\n",
+        "[token_lifetime]\ntimezone = \"US/Eastern\"\n",
+        "[token_lifetime]\nidle_timeout_minutes = -120\n",
+        "renew_access_token_url = \"not a url\"\n",
+    ] {
+        let dir = TempDir::new().unwrap();
+        write_config(&dir, &server, extra);
+        assert!(
+            matches!(
+                Builder::from_config_file(dir.path().join("oagain.toml")),
+                Err(OagainError::BadConfig(_))
+            ),
+            "{}",
+            extra
+        );
+    }
+
+    // A JSON store has nowhere to keep the profile.
+    let dir = TempDir::new().unwrap();
+    write_config(
+        &dir,
+        &server,
+        "profile = \"sandbox\"\n[store]\nkind = \"json\"\npath = \"save.json\"\n",
+    );
+    assert!(matches!(
+        Builder::from_config_file(dir.path().join("oagain.toml")),
+        Err(OagainError::ProfileUnsupported(_))
+    ));
+
+    // Typos aren't silently ignored.
+    let dir = TempDir::new().unwrap();
+    write_config(&dir, &server, "acess_token_url = \"https://example.com\"\n");
+    assert!(matches!(
+        Builder::from_config_file(dir.path().join("oagain.toml")),
+        Err(OagainError::TomlError(_))
+    ));
+}
@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+use std::env::VarError;
+use std::fs;
+
+use tempfile::TempDir;
+
+use crate::consumer::test_cases::{build, unauthed_builder, ACCESS_TOKEN, TOKEN_SECRET};
+use crate::error::OagainError;
+use crate::test_server::TestServer;
+
+// The variables are looked up in a map, since the real environment is shared by the tests running
+// in parallel, and changing it while another thread reads it is a data race.
+fn lookup(vars: &[(&str, &str)]) -> impl Fn(&str) -> Result<String, VarError> {
+    let vars = vars
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect::<HashMap<_, _>>();
+    move |name| vars.get(name).cloned().ok_or(VarError::NotPresent)
+}
+
+#[test]
+fn env_overrides_secrets_file() {
+    let dir = TempDir::new().unwrap();
+    let secrets_path = dir.path().join("secrets.toml");
+    fs::write(
+        &secrets_path,
+        "token = \"file-key\"\nsecret = \"file-secret\"\n",
+    )
+    .unwrap();
+    let vars = lookup(&[
+        ("OAGAIN_CONSUMER_KEY", "env-key"),
+        ("OAGAIN_CONSUMER_SECRET", ""),
+        ("OAGAIN_ACCESS_TOKEN", ACCESS_TOKEN),
+        ("OAGAIN_TOKEN_SECRET", TOKEN_SECRET),
+        (
+            "OAGAIN_REVOKE_ACCESS_TOKEN_URL",
+            "https://example.com/revoke",
+        ),
+    ]);
+    let server = TestServer::start(vec![]);
+
+    let consumer = build(
+        unauthed_builder(&server)
+            .use_secrets_file(&secrets_path)
+            .unwrap()
+            .use_env_with("OAGAIN", &vars)
+            .unwrap(),
+    );
+    assert_eq!("env-key", consumer.config.consumer_key);
+    // Empty variables are ignored.
+    assert_eq!("file-secret", consumer.config.consumer_secret);
+    assert!(consumer.is_fully_authed());
+    assert_eq!(
+        "https://example.com/revoke",
+        consumer.config.revoke_access_token_url.unwrap().as_str()
+    );
+
+    // Later calls win.
+    let consumer = build(
+        unauthed_builder(&server)
+            .use_env_with("OAGAIN", &vars)
+            .unwrap()
+            .use_secrets_file(&secrets_path)
+            .unwrap(),
+    );
+    assert_eq!("file-key", consumer.config.consumer_key);
+}
+
+#[test]
+fn env_token_needs_secret() {
+    let vars = lookup(&[("OAGAIN_ACCESS_TOKEN", ACCESS_TOKEN)]);
+    let server = TestServer::start(vec![]);
+
+    assert!(matches!(
+        unauthed_builder(&server).use_env_with("OAGAIN", &vars),
+        Err(OagainError::MissingEnvVar(name)) if name == "OAGAIN_TOKEN_SECRET"
+    ));
+}
+
+#[test]
+fn env_bad_url() {
+    let vars = lookup(&[("OAGAIN_ACCESS_TOKEN_URL", "not a url")]);
+    let server = TestServer::start(vec![]);
+
+    assert!(matches!(
+        unauthed_builder(&server).use_env_with("OAGAIN", &vars),
+        Err(OagainError::BadEnvVar(name)) if name == "OAGAIN_ACCESS_TOKEN_URL"
+    ));
+}
+
+#[test]
+fn env_not_unicode() {
+    let vars = |_: &str| Err(VarError::NotUnicode("\u{fffd}".into()));
+    let server = TestServer::start(vec![]);
+
+    assert!(matches!(
+        unauthed_builder(&server).use_env_with("OAGAIN", &vars),
+        Err(OagainError::BadEnvVar(name)) if name == "OAGAIN_CONSUMER_KEY"
+    ));
+}
@@ -0,0 +1,82 @@
+use url::Url;
+
+use crate::consumer::test_cases::{
+    access_token_response, authed_consumer, build, unauthed_builder, ACCESS_TOKEN,
+};
+use crate::consumer::{Headless, UserAuthorizer};
+use crate::error::{OagainError, Result};
+use crate::test_server::{CannedResponse, TestServer};
+
+#[test]
+fn typestate_flow() {
+    let server = TestServer::start(vec![
+        CannedResponse::ok("oauth_token=hh5s93j4hdidpola&oauth_token_secret=hdhd0244k9j7ao03"),
+        access_token_response(),
+        CannedResponse::ok("photos"),
+    ]);
+    let Err(flow) = build(unauthed_builder(&server)).into_authorized() else {
+        panic!("the consumer has no access token");
+    };
+
+    let flow = flow.retrieve_request_token().unwrap();
+    assert_eq!("hh5s93j4hdidpola", flow.request_token());
+    assert!(flow
+        .user_auth_url()
+        .unwrap()
+        .as_str()
+        .starts_with(server.url("/authorize").as_str()));
+    let consumer = flow
+        .set_verification_code("hfdp7dh39dks9884")
+        .retrieve_access_token()
+        .unwrap();
+
+    assert_eq!(ACCESS_TOKEN, consumer.access_token().unwrap().token);
+    assert_eq!("photos", consumer.get(&server.url("/photos")).unwrap());
+    assert!(consumer.into_consumer().is_fully_authed());
+}
+
+#[test]
+fn failed_step_can_be_retried() {
+    let server = TestServer::start(vec![
+        CannedResponse::new(503, "try later"),
+        CannedResponse::ok("oauth_token=hh5s93j4hdidpola&oauth_token_secret=hdhd0244k9j7ao03"),
+    ]);
+    let Err(flow) = build(unauthed_builder(&server)).into_authorized() else {
+        panic!("the consumer has no access token");
+    };
+
+    let err = flow.retrieve_request_token().unwrap_err();
+    assert!(matches!(err.error, OagainError::HttpStatus { .. }));
+    let flow = err.state.retrieve_request_token().unwrap();
+    assert_eq!("hh5s93j4hdidpola", flow.request_token());
+}
+
+#[test]
+fn authorize_with() {
+    #[derive(Debug)]
+    struct User;
+    impl UserAuthorizer for User {
+        fn authorize(&mut self, _: &Url, request_token: &str) -> Result<String> {
+            assert_eq!("hh5s93j4hdidpola", request_token);
+            Ok("hfdp7dh39dks9884".to_string())
+        }
+    }
+
+    let server = TestServer::start(vec![CannedResponse::ok(
+        "oauth_token=hh5s93j4hdidpola&oauth_token_secret=hdhd0244k9j7ao03",
+    )]);
+    let Err(flow) = build(unauthed_builder(&server)).into_authorized() else {
+        panic!("the consumer has no access token");
+    };
+    let flow = flow.retrieve_request_token().unwrap();
+
+    let err = flow.authorize_with(&mut Headless).unwrap_err();
+    assert!(matches!(err.error, OagainError::NeedsUserAuth(_)));
+    err.state.authorize_with(&mut User).unwrap();
+}
+
+#[test]
+fn already_authorized() {
+    let server = TestServer::start(vec![]);
+    assert!(authed_consumer(&server).into_authorized().is_ok());
+}
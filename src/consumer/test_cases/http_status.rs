@@ -0,0 +1,122 @@
+use reqwest::{Method, StatusCode};
+
+use crate::consumer::test_cases::{authed_consumer, build, unauthed_builder};
+use crate::error::OagainError;
+use crate::problem::OauthProblem;
+use crate::test_server::{CannedResponse, TestServer};
+
+#[test]
+fn get_unauthorized() {
+    let server =
+        TestServer::start(vec![CannedResponse::new(401, "go away")
+            .header("WWW-Authenticate", "OAuth realm=\"photos\"")]);
+    let consumer = authed_consumer(&server);
+
+    let Err(OagainError::HttpStatus {
+        status,
+        headers,
+        body,
+    }) = consumer.get(&server.url("/photos"))
+    else {
+        panic!("expected an HttpStatus error");
+    };
+    assert_eq!(StatusCode::UNAUTHORIZED, status);
+    assert_eq!(
+        "OAuth realm=\"photos\"",
+        headers.get("www-authenticate").unwrap()
+    );
+    assert_eq!("go away", body);
+}
+
+#[test]
+fn every_request_path_checks_status() {
+    let server = TestServer::start(vec![
+        CannedResponse::new(500, ""),
+        CannedResponse::new(404, ""),
+        CannedResponse::new(403, ""),
+        CannedResponse::new(302, ""),
+    ]);
+    let consumer = authed_consumer(&server);
+    let url = server.url("/orders");
+
+    let statuses = [
+        consumer.post_form(&url, [("a", "1")]).unwrap_err(),
+        consumer
+            .send_body(Method::PUT, &url, "application/json", "{}")
+            .unwrap_err(),
+        consumer.head(&url).unwrap_err(),
+        consumer.request(Method::DELETE, &url).send().unwrap_err(),
+    ]
+    .map(|err| match err {
+        OagainError::HttpStatus { status, .. } => status.as_u16(),
+        _ => panic!("expected an HttpStatus error, got {:?}", err),
+    });
+    assert_eq!([500, 404, 403, 302], statuses);
+}
+
+#[test]
+fn request_token_unauthorized() {
+    // Without the status check, this would look like a missing oauth_token.
+    let server = TestServer::start(vec![CannedResponse::new(
+        401,
+        "oauth_problem=consumer_key_unknown",
+    )]);
+    let consumer = build(unauthed_builder(&server));
+
+    assert!(matches!(
+        consumer.retrieve_request_token(),
+        Err(OagainError::OauthProblem { status, report })
+            if status == StatusCode::UNAUTHORIZED
+                && report.problem == OauthProblem::ConsumerKeyUnknown
+    ));
+}
+
+#[test]
+fn oauth_problems() {
+    let server = TestServer::start(vec![
+        CannedResponse::new(
+            401,
+            "oauth_problem=timestamp_refused&oauth_acceptable_timestamps=1191242000-1191242200",
+        ),
+        CannedResponse::new(401, "<html>Unauthorized</html>").header(
+            "WWW-Authenticate",
+            "OAuth realm=\"photos\", oauth_problem=\"token_expired\"",
+        ),
+    ]);
+    let consumer = authed_consumer(&server);
+    let url = server.url("/photos");
+
+    let Err(OagainError::OauthProblem { status, report }) = consumer.get(&url) else {
+        panic!("expected an OauthProblem error");
+    };
+    assert_eq!(StatusCode::UNAUTHORIZED, status);
+    assert_eq!(OauthProblem::TimestampRefused, report.problem);
+    assert_eq!(Some((1191242000, 1191242200)), report.acceptable_timestamps);
+
+    let err = consumer.get(&url).unwrap_err();
+    assert_eq!(Some(&OauthProblem::TokenExpired), err.oauth_problem());
+}
+
+#[test]
+fn request_token_missing() {
+    let server = TestServer::start(vec![CannedResponse::ok("oauth_token_secret=abc")]);
+    let consumer = build(unauthed_builder(&server));
+
+    assert!(matches!(
+        consumer.retrieve_request_token(),
+        Err(OagainError::MissingRequestToken)
+    ));
+}
+
+#[test]
+fn malformed_token_response() {
+    let server = TestServer::start(vec![CannedResponse::ok(
+        "oauth_token=%zz&oauth_token_secret=hdhd0244k9j7ao03",
+    )]);
+    let consumer = build(unauthed_builder(&server));
+
+    assert!(matches!(
+        consumer.retrieve_request_token(),
+        Err(OagainError::BadTokenResponse)
+    ));
+}
@@ -0,0 +1,141 @@
+mod authorizer;
+mod body_hash;
+mod callback;
+mod config;
+mod env;
+mod flow;
+mod http_status;
+mod multi_user;
+mod pending;
+mod profile;
+mod renewal;
+mod request_builder;
+mod revoke;
+mod threads;
+mod token_lifetime;
+mod token_store;
+mod verbs;
+
+use std::fs::{self, Permissions};
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+use chrono::{DateTime, Duration, Utc};
+use url::Url;
+
+use crate::consumer::{Builder, Consumer};
+use crate::nonce_provider::{BasicNonce, TestEpochProvider};
+use crate::parameters::ParamPair;
+use crate::signing::{concat_request_elements, HmacSha1, SignatureMethod};
+use crate::test_server::{CannedResponse, RecordedRequest, TestServer};
+use crate::token_store::SavedToken;
+
+pub(crate) const CONSUMER_KEY: &str = "dpf43f3p2l4k3l03";
+pub(crate) const CONSUMER_SECRET: &str = "kd94hf93k423kf44";
+pub(crate) const ACCESS_TOKEN: &str = "nnch734d00sl2jdk";
+pub(crate) const TOKEN_SECRET: &str = "pfkkdhi9sl3r4s00";
+
+/// The provider's answer to an access token request, granting ACCESS_TOKEN.
+pub(crate) fn access_token_response() -> CannedResponse {
+    CannedResponse::ok(format!(
+        "oauth_token={}&oauth_token_secret={}",
+        ACCESS_TOKEN, TOKEN_SECRET
+    ))
+}
+
+/// ACCESS_TOKEN as a token store keeps it, issued and last used on 2023-12-01. Tests that need a
+/// fresh token replace the times.
+pub(crate) fn saved_token() -> SavedToken {
+    SavedToken {
+        access_token: ACCESS_TOKEN.to_string(),
+        token_secret: TOKEN_SECRET.to_string(),
+        issued: "2023-12-01T10:00:00Z".parse::<DateTime<Utc>>().unwrap(),
+        last_used: "2023-12-01T11:30:00Z".parse::<DateTime<Utc>>().unwrap(),
+    }
+}
+
+/// A consumer whose endpoints all point at `server`, and which already has an access token.
+pub(crate) fn authed_consumer(server: &TestServer) -> Consumer<BasicNonce<TestEpochProvider>> {
+    build(authed_builder(server))
+}
+
+pub(crate) fn build(builder: Builder) -> Consumer<BasicNonce<TestEpochProvider>> {
+    builder
+        .build_with_nonce_provider(BasicNonce::new(TestEpochProvider::new(1191242096)))
+        .unwrap()
+}
+
+pub(crate) fn authed_builder(server: &TestServer) -> Builder {
+    unauthed_builder(server).set_access_token(ACCESS_TOKEN, TOKEN_SECRET)
+}
+
+/// A builder whose endpoints all point at `server`, with no tokens.
+pub(crate) fn unauthed_builder(server: &TestServer) -> Builder {
+    Consumer::<BasicNonce<TestEpochProvider>>::builder()
+        .set_request_token_url(server.url("/request_token"))
+        .unwrap()
+        .set_user_authorization_url(server.url("/authorize"))
+        .unwrap()
+        .set_access_token_url(server.url("/access_token"))
+        .unwrap()
+        .set_consumer_key(CONSUMER_KEY)
+        .set_consumer_secret(CONSUMER_SECRET)
+}
+
+/// Checks the signature of a request received by the test server, as the provider would.
+pub(crate) fn signature_is_valid(request: &RecordedRequest, body_params: &[ParamPair]) -> bool {
+    let url = &request.url;
+    let mut params = request.oauth_params();
+    let signature = params.pop().unwrap().value.unwrap();
+
+    let query_params = url
+        .query_pairs()
+        .map(|(name, value)| ParamPair::pair(name, value))
+        .collect::<Vec<_>>();
+    let base_string = concat_request_elements(
+        &request.method,
+        url,
+        params
+            .iter()
+            .chain(query_params.iter())
+            .chain(body_params.iter())
+            .cloned(),
+    );
+    HmacSha1
+        .verify(&signature, CONSUMER_SECRET, TOKEN_SECRET, &base_string)
+        .unwrap()
+}
+
+pub(crate) fn url_with_query(server: &TestServer, path: &str, query: &str) -> Url {
+    let mut url = server.url(path);
+    url.set_query(Some(query));
+    url
+}
+
+/// Writes a save file holding the test access token, saved `age` ago.
+pub(crate) fn write_save_file(path: &Path, age: Duration) {
+    let save_time = (Utc::now() - age).format("%Y-%m-%dT%H:%M:%SZ");
+    fs::write(
+        path,
+        format!(
+            "access_token = \"{}\"\ntoken_secret = \"{}\"\ntoken_save_time = {}\n",
+            ACCESS_TOKEN, TOKEN_SECRET, save_time
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(path, Permissions::from_mode(0o600)).unwrap();
+}
+
+/// Reads a time from a save file.
+pub(crate) fn saved_time(path: &Path, name: &str) -> DateTime<Utc> {
+    let table = fs::read_to_string(path)
+        .unwrap()
+        .parse::<toml::Table>()
+        .unwrap();
+    let Some(toml::Value::Datetime(save_time)) = table.get(name) else {
+        panic!("no {} in the save file", name);
+    };
+    DateTime::parse_from_rfc3339(&save_time.to_string())
+        .unwrap()
+        .with_timezone(&Utc)
+}
@@ -0,0 +1,161 @@
+use reqwest::blocking::Client;
+
+use crate::consumer::test_cases::{
+    signature_is_valid, unauthed_builder, ACCESS_TOKEN, TOKEN_SECRET,
+};
+use crate::consumer::{AccessToken, ConsumerConfig};
+use crate::error::Result;
+use crate::nonce_provider::{BasicNonce, TestEpochProvider};
+use crate::parameters::ParamPair;
+use crate::test_server::{CannedResponse, RecordedRequest, TestServer};
+use crate::token_store::{KeyedMemoryStore, KeyedTokenStore, SavedToken, TokenStore};
+
+fn config(server: &TestServer) -> ConsumerConfig<BasicNonce<TestEpochProvider>> {
+    unauthed_builder(server)
+        .build_config_with_nonce_provider(BasicNonce::new(TestEpochProvider::new(1191242096)))
+        .unwrap()
+}
+
+fn param(request: &RecordedRequest, name: &str) -> Option<String> {
+    request
+        .oauth_params()
+        .into_iter()
+        .find(|p| p.name == name)
+        .and_then(|p| p.value)
+}
+
+#[test]
+fn one_config_signs_for_many_users() {
+    let server = TestServer::start(vec![CannedResponse::ok("one"), CannedResponse::ok("two")]);
+    let config = config(&server);
+    let alice = AccessToken {
+        token: ACCESS_TOKEN.to_string(),
+        secret: TOKEN_SECRET.to_string(),
+    };
+    let bob = AccessToken {
+        token: "bobs-token".to_string(),
+        secret: "bobs-secret".to_string(),
+    };
+    let client = Client::new();
+
+    let request = client.get(server.url("/photos")).build().unwrap();
+    assert_eq!("one", config.send(&alice, request).unwrap().text().unwrap());
+    let request = client.get(server.url("/photos")).build().unwrap();
+    assert_eq!("two", config.send(&bob, request).unwrap().text().unwrap());
+
+    let requests = server.requests();
+    assert_eq!(
+        Some(ACCESS_TOKEN.to_string()),
+        param(&requests[0], "oauth_token")
+    );
+    assert!(signature_is_valid(&requests[0], &[]));
+    assert_eq!(
+        Some("bobs-token".to_string()),
+        param(&requests[1], "oauth_token")
+    );
+}
+
+#[test]
+fn form_body_is_signed() {
+    let server = TestServer::start(vec![CannedResponse::ok("")]);
+    let config = config(&server);
+    let token = AccessToken {
+        token: ACCESS_TOKEN.to_string(),
+        secret: TOKEN_SECRET.to_string(),
+    };
+    let mut request = Client::new()
+        .post(server.url("/photos"))
+        .form(&[("size", "original")])
+        .build()
+        .unwrap();
+    config.sign(&token, &mut request).unwrap();
+    Client::new().execute(request).unwrap();
+
+    let requests = server.requests();
+    assert!(signature_is_valid(
+        &requests[0],
+        &[ParamPair::pair("size", "original")]
+    ));
+}
+
+#[test]
+fn users_authorize_independently() {
+    let server = TestServer::start(vec![
+        CannedResponse::ok("oauth_token=alice-request&oauth_token_secret=alice-rsecret"),
+        CannedResponse::ok("oauth_token=bob-request&oauth_token_secret=bob-rsecret"),
+        CannedResponse::ok("oauth_token=bob-access&oauth_token_secret=bob-secret"),
+        CannedResponse::ok("oauth_token=alice-access&oauth_token_secret=alice-secret"),
+    ]);
+    let config = config(&server);
+    let store = KeyedMemoryStore::new();
+
+    let alice_pending = config.retrieve_request_token().unwrap();
+    let bob_pending = config.retrieve_request_token().unwrap();
+    assert!(config
+        .user_auth_url(&bob_pending)
+        .query_pairs()
+        .any(|(name, value)| name == "oauth_token" && value == "bob-request"));
+
+    // Bob finishes first.
+    for (key, pending, verifier) in [
+        ("bob", &bob_pending, "bob-verifier"),
+        ("alice", &alice_pending, "alice-verifier"),
+    ] {
+        let token = config.retrieve_access_token(pending, verifier).unwrap();
+        let now = chrono::Utc::now();
+        let saved = SavedToken {
+            access_token: token.token,
+            token_secret: token.secret,
+            issued: now,
+            last_used: now,
+        };
+        store.save(key, &saved).unwrap();
+    }
+
+    let alice = AccessToken::from(&store.load("alice").unwrap().unwrap());
+    assert_eq!("alice-access", alice.token);
+    assert_eq!("alice-secret", alice.secret);
+
+    let requests = server.requests();
+    assert_eq!(
+        Some("bob-request".to_string()),
+        param(&requests[2], "oauth_token")
+    );
+    assert_eq!(
+        Some("bob-verifier".to_string()),
+        param(&requests[2], "oauth_verifier")
+    );
+    assert_eq!(
+        Some("alice-request".to_string()),
+        param(&requests[3], "oauth_token")
+    );
+}
+
+// Stands in for a save file that is corrupt or locked by another process.
+#[derive(Debug)]
+struct UnusableStore;
+
+impl TokenStore for UnusableStore {
+    fn load(&self) -> Result<Option<SavedToken>> {
+        panic!("the token store was loaded");
+    }
+
+    fn save(&self, _token: &SavedToken) -> Result<()> {
+        panic!("the token store was saved to");
+    }
+
+    fn clear(&self) -> Result<()> {
+        panic!("the token store was cleared");
+    }
+}
+
+#[test]
+fn config_does_not_open_token_store() {
+    let server = TestServer::start(vec![]);
+
+    let config = unauthed_builder(&server)
+        .use_token_store(UnusableStore)
+        .build_config()
+        .unwrap();
+    assert_eq!(server.url("/request_token"), config.request_token_url);
+}
@@ -0,0 +1,60 @@
+use crate::consumer::test_cases::{access_token_response, build, unauthed_builder};
+use crate::consumer::{PendingAuthorization, RequestTokenHeld, Unauthorized};
+use crate::test_server::{CannedResponse, TestServer};
+
+fn param(server: TestServer, index: usize, name: &str) -> Option<String> {
+    server.requests()[index]
+        .oauth_params()
+        .into_iter()
+        .find(|p| p.name == name)
+        .and_then(|p| p.value)
+}
+
+#[test]
+fn authorization_resumes_in_another_consumer() {
+    let start_server = TestServer::start(vec![CannedResponse::ok(
+        "oauth_token=hh5s93j4hdidpola&oauth_token_secret=hdhd0244k9j7ao03",
+    )]);
+    let consumer = build(unauthed_builder(&start_server));
+    assert_eq!(None, consumer.pending_authorization());
+    consumer.retrieve_request_token().unwrap();
+    let snapshot = serde_json::to_string(&consumer.pending_authorization().unwrap()).unwrap();
+    drop(consumer);
+
+    // The user comes back with the verifier, in another request.
+    let finish_server = TestServer::start(vec![access_token_response()]);
+    let pending = serde_json::from_str::<PendingAuthorization>(&snapshot).unwrap();
+    assert_eq!("hh5s93j4hdidpola", pending.request_token);
+    let consumer = build(unauthed_builder(&finish_server));
+    consumer
+        .resume_authorization(pending, "hfdp7dh39dks9884")
+        .unwrap();
+
+    assert!(consumer.is_fully_authed());
+    assert_eq!(
+        Some("hh5s93j4hdidpola".to_string()),
+        param(finish_server, 0, "oauth_token")
+    );
+}
+
+#[test]
+fn typestate_flow_resumes() {
+    let server = TestServer::start(vec![
+        CannedResponse::ok("oauth_token=hh5s93j4hdidpola&oauth_token_secret=hdhd0244k9j7ao03"),
+        access_token_response(),
+    ]);
+    let pending = Unauthorized::new(build(unauthed_builder(&server)))
+        .retrieve_request_token()
+        .unwrap()
+        .pending_authorization();
+
+    let consumer = RequestTokenHeld::resume(build(unauthed_builder(&server)), pending)
+        .set_verification_code("hfdp7dh39dks9884")
+        .retrieve_access_token()
+        .unwrap();
+    assert!(consumer.access_token().is_some());
+    assert_eq!(
+        Some("hfdp7dh39dks9884".to_string()),
+        param(server, 1, "oauth_verifier")
+    );
+}
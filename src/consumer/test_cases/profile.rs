@@ -0,0 +1,108 @@
+use std::fs;
+
+use chrono::Utc;
+use tempfile::TempDir;
+
+use crate::consumer::test_cases::{build, saved_token, unauthed_builder};
+use crate::error::OagainError;
+use crate::test_server::TestServer;
+use crate::token_store::{Passphrase, SavedToken, TokenStore, TomlFileStore};
+
+const SECRETS: &str = r#"
+token = "production-key"
+secret = "production-secret"
+
+[profile.sandbox]
+token = "sandbox-key"
+secret = "sandbox-secret"
+"#;
+
+#[test]
+fn profile_is_selected() {
+    let dir = TempDir::new().unwrap();
+    let secrets_path = dir.path().join("secrets.toml");
+    let save_path = dir.path().join("save.toml");
+    fs::write(&secrets_path, SECRETS).unwrap();
+    let now = Utc::now();
+    TomlFileStore::new(&save_path)
+        .unwrap()
+        .with_profile("sandbox")
+        .save(&SavedToken {
+            issued: now,
+            last_used: now,
+            ..saved_token()
+        })
+        .unwrap();
+    let server = TestServer::start(vec![]);
+
+    let consumer = build(
+        unauthed_builder(&server)
+            .use_profile("sandbox")
+            .use_secrets_file(&secrets_path)
+            .unwrap()
+            .use_save_file(&save_path)
+            .unwrap(),
+    );
+    assert_eq!("sandbox-key", consumer.config.consumer_key);
+    assert!(consumer.is_fully_authed());
+
+    // The top level is used without a profile.
+    let consumer = build(
+        unauthed_builder(&server)
+            .use_secrets_file(&secrets_path)
+            .unwrap()
+            .use_save_file(&save_path)
+            .unwrap(),
+    );
+    assert_eq!("production-key", consumer.config.consumer_key);
+    assert!(!consumer.is_fully_authed());
+
+    // The profile can come after the files.
+    let consumer = build(
+        unauthed_builder(&server)
+            .use_secrets_file(&secrets_path)
+            .unwrap()
+            .use_save_file(&save_path)
+            .unwrap()
+            .use_profile("sandbox"),
+    );
+    assert_eq!("sandbox-key", consumer.config.consumer_key);
+    assert!(consumer.is_fully_authed());
+}
+
+#[test]
+fn encrypted_save_file_has_no_profiles() {
+    let dir = TempDir::new().unwrap();
+    let server = TestServer::start(vec![]);
+
+    let result = unauthed_builder(&server)
+        .use_profile("sandbox")
+        .use_encrypted_save_file(
+            dir.path().join("save.toml"),
+            Passphrase::callback(|| Ok("correct horse".to_string())),
+        )
+        .unwrap()
+        .build();
+    assert!(matches!(result, Err(OagainError::ProfileUnsupported(_))));
+}
+
+#[test]
+fn missing_profile() {
+    let dir = TempDir::new().unwrap();
+    let secrets_path = dir.path().join("secrets.toml");
+    fs::write(&secrets_path, SECRETS).unwrap();
+    let server = TestServer::start(vec![]);
+
+    let err = unauthed_builder(&server)
+        .use_profile("staging")
+        .use_secrets_file(&secrets_path)
+        .unwrap()
+        .build()
+        .unwrap_err();
+    assert!(matches!(
+        &err,
+        OagainError::InProfile { profile, source }
+            if profile == "staging" && matches!(**source, OagainError::MissingProfile(_))
+    ));
+    assert!(err.to_string().starts_with("In profile staging:"));
+}
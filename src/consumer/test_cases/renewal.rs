@@ -0,0 +1,106 @@
+use chrono::{Duration, Utc};
+use tempfile::TempDir;
+
+use crate::consumer::test_cases::{
+    authed_builder, build, saved_time, signature_is_valid, unauthed_builder, write_save_file,
+};
+use crate::consumer::Builder;
+use crate::error::OagainError;
+use crate::test_server::{CannedResponse, TestServer};
+use crate::token_lifetime::{FixedAge, IdleTimeout};
+
+// eTrade's limits: tokens need renewal after two idle hours, and expire after a day.
+fn idle_builder(server: &TestServer) -> Builder {
+    unauthed_builder(server).set_token_lifetime_policy((
+        IdleTimeout(Duration::hours(2)),
+        FixedAge(Duration::hours(24)),
+    ))
+}
+
+fn renewing_builder(server: &TestServer) -> Builder {
+    idle_builder(server)
+        .set_renew_access_token_url(server.url("/renew_access_token"))
+        .unwrap()
+}
+
+#[test]
+fn renew_access_token() {
+    let server = TestServer::start(vec![CannedResponse::ok("Access Token has been renewed")]);
+    let consumer = build(
+        authed_builder(&server)
+            .set_renew_access_token_url(server.url("/renew_access_token"))
+            .unwrap(),
+    );
+
+    consumer.renew_access_token().unwrap();
+
+    let requests = server.requests();
+    assert_eq!("/renew_access_token", requests[0].url.path());
+    assert!(signature_is_valid(&requests[0], &[]));
+}
+
+#[test]
+fn renew_without_url() {
+    let server = TestServer::start(vec![]);
+    let consumer = build(authed_builder(&server));
+
+    assert!(matches!(
+        consumer.renew_access_token(),
+        Err(OagainError::MissingRenewAccessTokenUrl)
+    ));
+}
+
+#[test]
+fn stale_saved_token_is_renewed() {
+    let dir = TempDir::new().unwrap();
+    let save_file = dir.path().join("save.toml");
+    write_save_file(&save_file, Duration::hours(3));
+
+    let server = TestServer::start(vec![
+        CannedResponse::ok("Access Token has been renewed"),
+        CannedResponse::ok("accounts"),
+    ]);
+    let consumer = build(renewing_builder(&server).use_save_file(&save_file).unwrap());
+    assert!(consumer.is_fully_authed());
+
+    assert_eq!("accounts", consumer.get(&server.url("/accounts")).unwrap());
+
+    let requests = server.requests();
+    assert_eq!("/renew_access_token", requests[0].url.path());
+    assert_eq!("/accounts", requests[1].url.path());
+    assert!(requests.iter().all(|r| signature_is_valid(r, &[])));
+    // Renewal doesn't change when the token was issued, but does count as a use.
+    assert!(Utc::now() - saved_time(&save_file, "token_save_time") > Duration::hours(2));
+    assert!(Utc::now() - saved_time(&save_file, "token_last_use") < Duration::minutes(1));
+}
+
+#[test]
+fn fresh_saved_token_is_not_renewed() {
+    let dir = TempDir::new().unwrap();
+    let save_file = dir.path().join("save.toml");
+    write_save_file(&save_file, Duration::minutes(30));
+
+    let server = TestServer::start(vec![CannedResponse::ok("accounts")]);
+    let consumer = build(renewing_builder(&server).use_save_file(&save_file).unwrap());
+
+    assert_eq!("accounts", consumer.get(&server.url("/accounts")).unwrap());
+    assert_eq!("/accounts", server.requests()[0].url.path());
+}
+
+#[test]
+fn unrenewable_saved_tokens_are_dropped() {
+    let dir = TempDir::new().unwrap();
+    let server = TestServer::start(vec![]);
+
+    // Too old to renew.
+    let expired = dir.path().join("expired.toml");
+    write_save_file(&expired, Duration::hours(25));
+    let consumer = build(renewing_builder(&server).use_save_file(&expired).unwrap());
+    assert!(!consumer.is_fully_authed());
+
+    // Renewable, but there's no renew URL.
+    let stale = dir.path().join("stale.toml");
+    write_save_file(&stale, Duration::hours(3));
+    let consumer = build(idle_builder(&server).use_save_file(&stale).unwrap());
+    assert!(!consumer.is_fully_authed());
+}
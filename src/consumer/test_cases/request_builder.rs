@@ -0,0 +1,146 @@
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+
+use crate::consumer::test_cases::{authed_consumer, signature_is_valid, url_with_query};
+use crate::error::OagainError;
+use crate::parameters::ParamPair;
+use crate::signing::hash_body;
+use crate::test_server::{CannedResponse, TestServer};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Quote {
+    symbol: String,
+    price: f64,
+}
+
+#[test]
+fn structured_response() {
+    let server = TestServer::start(vec![CannedResponse::new(
+        201,
+        r#"{"symbol":"IBM","price":161.5}"#,
+    )
+    .header("Content-Type", "application/json")
+    .header("X-Request-Id", "abc")]);
+    let consumer = authed_consumer(&server);
+
+    let url = url_with_query(&server, "/v1/market/quote", "detail=ALL");
+    let response = consumer
+        .request(Method::GET, &url)
+        .query([("symbols", "IBM")])
+        .header("Accept", "application/json")
+        .send()
+        .unwrap();
+
+    assert_eq!(StatusCode::CREATED, response.status());
+    assert_eq!("abc", response.headers().get("x-request-id").unwrap());
+    assert_eq!(br#"{"symbol":"IBM","price":161.5}"#, response.bytes());
+    assert_eq!(
+        r#"{"symbol":"IBM","price":161.5}"#,
+        response.text().unwrap()
+    );
+    assert_eq!(
+        Quote {
+            symbol: "IBM".to_string(),
+            price: 161.5
+        },
+        response.json::<Quote>().unwrap()
+    );
+
+    let requests = server.requests();
+    assert_eq!(Some("detail=ALL&symbols=IBM"), requests[0].url.query());
+    assert_eq!(Some("application/json"), requests[0].header("accept"));
+    assert!(signature_is_valid(&requests[0], &[]));
+}
+
+#[test]
+fn form_body() {
+    let server = TestServer::start(vec![CannedResponse::ok("")]);
+    let consumer = authed_consumer(&server);
+
+    consumer
+        .request(Method::POST, &server.url("/orders"))
+        .form([("symbol", "IBM")])
+        .form([("quantity", "10")])
+        .send()
+        .unwrap();
+
+    let requests = server.requests();
+    assert_eq!(b"symbol=IBM&quantity=10".to_vec(), requests[0].body);
+    assert!(signature_is_valid(
+        &requests[0],
+        &[
+            ParamPair::pair("symbol", "IBM"),
+            ParamPair::pair("quantity", "10")
+        ]
+    ));
+}
+
+#[test]
+fn json_body() {
+    let server = TestServer::start(vec![CannedResponse::ok("")]);
+    let consumer = authed_consumer(&server);
+    let quote = Quote {
+        symbol: "IBM".to_string(),
+        price: 161.5,
+    };
+
+    consumer
+        .request(Method::PUT, &server.url("/quotes"))
+        .json(&quote)
+        .send()
+        .unwrap();
+
+    let requests = server.requests();
+    let request = &requests[0];
+    assert_eq!(Some("application/json"), request.header("content-type"));
+    assert_eq!(serde_json::to_vec(&quote).unwrap(), request.body);
+    assert!(request.oauth_params().contains(&ParamPair::pair(
+        "oauth_body_hash",
+        hash_body::<Sha1>(&request.body)
+    )));
+    assert!(signature_is_valid(request, &[]));
+}
+
+#[test]
+fn raw_body_with_content_type() {
+    let server = TestServer::start(vec![CannedResponse::ok("")]);
+    let consumer = authed_consumer(&server);
+
+    consumer
+        .request(Method::POST, &server.url("/orders"))
+        .header("Content-Type", "application/xml")
+        .body("<order/>")
+        .send()
+        .unwrap();
+
+    let requests = server.requests();
+    assert_eq!(Some("application/xml"), requests[0].header("content-type"));
+    assert_eq!(b"<order/>".to_vec(), requests[0].body);
+}
+
+#[test]
+fn builder_errors_are_deferred() {
+    // No server needed: nothing is sent.
+    let server = TestServer::start(vec![]);
+    let consumer = authed_consumer(&server);
+    let url = server.url("/orders");
+
+    let result = consumer
+        .request(Method::POST, &url)
+        .form([("oauth_nonce", "mine")])
+        .send();
+    assert!(matches!(result, Err(OagainError::DisallowedOauthParam(_))));
+
+    let result = consumer
+        .request(Method::GET, &url)
+        .header("Authorization", "Basic Zm9vOmJhcg==")
+        .send();
+    assert!(matches!(result, Err(OagainError::BadHeader(_))));
+
+    let result = consumer
+        .request(Method::GET, &url)
+        .header("Bad Header", "value")
+        .send();
+    assert!(matches!(result, Err(OagainError::BadHeader(_))));
+}
@@ -0,0 +1,74 @@
+use std::fs;
+
+use tempfile::TempDir;
+
+use crate::consumer::test_cases::{authed_builder, build, signature_is_valid, unauthed_builder};
+use crate::consumer::Builder;
+use crate::error::OagainError;
+use crate::test_server::{CannedResponse, TestServer};
+
+fn revoking_builder(builder: Builder, server: &TestServer) -> Builder {
+    builder
+        .set_revoke_access_token_url(server.url("/revoke_access_token"))
+        .unwrap()
+}
+
+#[test]
+fn revoke() {
+    let dir = TempDir::new().unwrap();
+    let save_file = dir.path().join("save.toml");
+    let server = TestServer::start(vec![
+        CannedResponse::ok("oauth_token=abc&oauth_token_secret=def"),
+        CannedResponse::ok("Revoked Access Token"),
+    ]);
+    let consumer = build(
+        revoking_builder(unauthed_builder(&server), &server)
+            .use_save_file(&save_file)
+            .unwrap()
+            .set_access_token("ignored", "ignored"),
+    );
+    consumer.retrieve_access_token().unwrap();
+    assert!(!fs::read_to_string(&save_file).unwrap().is_empty());
+
+    consumer.revoke().unwrap();
+
+    assert!(!consumer.is_fully_authed());
+    assert!(fs::read_to_string(&save_file).unwrap().is_empty());
+    let requests = server.requests();
+    assert_eq!("/revoke_access_token", requests[1].url.path());
+    assert_eq!(
+        Some("abc"),
+        requests[1]
+            .oauth_params()
+            .iter()
+            .find(|p| p.name == "oauth_token")
+            .and_then(|p| p.value.as_deref())
+    );
+}
+
+#[test]
+fn revoke_refused() {
+    let server = TestServer::start(vec![CannedResponse::new(
+        401,
+        "oauth_problem=token_rejected",
+    )]);
+    let consumer = build(revoking_builder(authed_builder(&server), &server));
+
+    let err = consumer.revoke().unwrap_err();
+
+    assert!(err.oauth_problem().is_some());
+    assert!(!consumer.is_fully_authed());
+    assert!(signature_is_valid(&server.requests()[0], &[]));
+}
+
+#[test]
+fn revoke_without_url() {
+    let server = TestServer::start(vec![]);
+    let consumer = build(authed_builder(&server));
+
+    assert!(matches!(
+        consumer.revoke(),
+        Err(OagainError::MissingRevokeAccessTokenUrl)
+    ));
+    assert!(consumer.is_fully_authed());
+}
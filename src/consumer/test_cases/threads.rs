@@ -0,0 +1,149 @@
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use url::Url;
+
+use crate::consumer::test_cases::{
+    access_token_response, authed_builder, authed_consumer, build, signature_is_valid,
+    unauthed_builder, ACCESS_TOKEN, TOKEN_SECRET,
+};
+use crate::consumer::{BasicConsumer, Consumer, ConsumerConfig, UserAuthorizer};
+use crate::error::Result;
+use crate::nonce_provider::{BasicNonce, NonceProvider, SystemEpochProvider};
+use crate::test_server::{CannedResponse, TestServer};
+
+const THREADS: usize = 4;
+const REQUESTS_PER_THREAD: usize = 3;
+
+// Answers like a user who authorized, and counts how often it was asked.
+#[derive(Debug, Default, Clone)]
+struct CountingUser {
+    asked: Arc<AtomicUsize>,
+}
+
+impl UserAuthorizer for CountingUser {
+    fn authorize(&mut self, _: &Url, _: &str) -> Result<String> {
+        self.asked.fetch_add(1, Ordering::SeqCst);
+        Ok("hfdp7dh39dks9884".to_string())
+    }
+}
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+// GETs `url` from each thread, sharing `consumer`.
+fn get_from_threads<NP: NonceProvider>(consumer: &Consumer<NP>, url: &Url) {
+    thread::scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|| {
+                for _ in 0..REQUESTS_PER_THREAD {
+                    assert_eq!("ok", consumer.get(url).unwrap());
+                }
+            });
+        }
+    });
+}
+
+#[test]
+fn consumers_can_be_shared() {
+    assert_send_sync::<BasicConsumer>();
+    assert_send_sync::<ConsumerConfig<BasicNonce<SystemEpochProvider>>>();
+}
+
+#[test]
+fn concurrent_requests_get_unique_nonces() {
+    let server = TestServer::start(
+        (0..THREADS * REQUESTS_PER_THREAD)
+            .map(|_| CannedResponse::ok("ok"))
+            .collect(),
+    );
+    get_from_threads(&authed_consumer(&server), &server.url("/accounts"));
+
+    let requests = server.requests();
+    assert!(requests
+        .iter()
+        .all(|request| signature_is_valid(request, &[])));
+    let nonces = requests
+        .iter()
+        .map(|request| {
+            request
+                .oauth_params()
+                .into_iter()
+                .find(|p| p.name == "oauth_nonce")
+                .and_then(|p| p.value)
+                .unwrap()
+        })
+        .collect::<HashSet<_>>();
+    assert_eq!(THREADS * REQUESTS_PER_THREAD, nonces.len());
+}
+
+#[test]
+fn only_one_thread_authorizes() {
+    let mut responses = vec![
+        CannedResponse::ok("oauth_token=hh5s93j4hdidpola&oauth_token_secret=hdhd0244k9j7ao03"),
+        access_token_response(),
+    ];
+    responses.extend((0..THREADS * REQUESTS_PER_THREAD).map(|_| CannedResponse::ok("ok")));
+    let server = TestServer::start(responses);
+    let user = CountingUser::default();
+    let consumer = build(unauthed_builder(&server).set_user_authorizer(user.clone()));
+
+    get_from_threads(&consumer, &server.url("/accounts"));
+
+    assert_eq!(1, user.asked.load(Ordering::SeqCst));
+    let requests = server.requests();
+    assert_eq!("/request_token", requests[0].url.path());
+    assert_eq!("/access_token", requests[1].url.path());
+}
+
+#[test]
+fn requests_racing_revoke_are_signed_with_an_access_token() {
+    // The requests from the threads, the revocation, the authorization that follows it, and one
+    // last request, which makes sure the authorization happens even if the threads were faster.
+    let server = TestServer::serve(THREADS * REQUESTS_PER_THREAD + 4, |request| {
+        match request.url.path() {
+            "/request_token" => {
+                CannedResponse::ok("oauth_token=request-token&oauth_token_secret=request-secret")
+            }
+            "/access_token" => CannedResponse::ok(format!(
+                "oauth_token=new-token&oauth_token_secret={}",
+                TOKEN_SECRET
+            )),
+            _ => CannedResponse::ok("ok"),
+        }
+    });
+    let user = CountingUser::default();
+    let consumer = build(
+        authed_builder(&server)
+            .set_revoke_access_token_url(server.url("/revoke_access_token"))
+            .unwrap()
+            .set_user_authorizer(user.clone()),
+    );
+    let url = server.url("/accounts");
+
+    thread::scope(|scope| {
+        scope.spawn(|| consumer.revoke().unwrap());
+        get_from_threads(&consumer, &url);
+    });
+    assert_eq!("ok", consumer.get(&url).unwrap());
+
+    assert_eq!(1, user.asked.load(Ordering::SeqCst));
+    let tokens = server
+        .requests()
+        .iter()
+        .filter(|request| request.url.path() == "/accounts")
+        .map(|request| {
+            assert!(signature_is_valid(request, &[]));
+            request
+                .oauth_params()
+                .into_iter()
+                .find(|p| p.name == "oauth_token")
+                .and_then(|p| p.value)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(THREADS * REQUESTS_PER_THREAD + 1, tokens.len());
+    assert!(tokens
+        .iter()
+        .all(|token| matches!(token.as_deref(), Some(ACCESS_TOKEN) | Some("new-token"))));
+}
@@ -0,0 +1,55 @@
+use chrono::{Duration, Utc};
+use tempfile::TempDir;
+
+use crate::consumer::test_cases::{
+    authed_consumer, build, saved_time, unauthed_builder, write_save_file,
+};
+use crate::test_server::{CannedResponse, TestServer};
+use crate::token_lifetime::{FixedAge, NeverExpires};
+
+#[test]
+fn last_use_is_saved() {
+    let dir = TempDir::new().unwrap();
+    let save_file = dir.path().join("save.toml");
+    write_save_file(&save_file, Duration::minutes(90));
+
+    let server = TestServer::start(vec![CannedResponse::ok("accounts")]);
+    let consumer = build(unauthed_builder(&server).use_save_file(&save_file).unwrap());
+    consumer.get(&server.url("/accounts")).unwrap();
+
+    assert!(Utc::now() - saved_time(&save_file, "token_save_time") > Duration::minutes(89));
+    assert!(Utc::now() - saved_time(&save_file, "token_last_use") < Duration::minutes(1));
+}
+
+#[test]
+fn failed_requests_are_not_a_use() {
+    let server = TestServer::start(vec![CannedResponse::new(500, "")]);
+    let consumer = authed_consumer(&server);
+
+    assert!(consumer.get(&server.url("/accounts")).is_err());
+    assert_eq!(None, consumer.session().token_times);
+}
+
+#[test]
+fn custom_policy() {
+    let dir = TempDir::new().unwrap();
+    let save_file = dir.path().join("save.toml");
+    write_save_file(&save_file, Duration::days(30));
+    let server = TestServer::start(vec![]);
+
+    let consumer = build(
+        unauthed_builder(&server)
+            .set_token_lifetime_policy(NeverExpires)
+            .use_save_file(&save_file)
+            .unwrap(),
+    );
+    assert!(consumer.is_fully_authed());
+
+    let consumer = build(
+        unauthed_builder(&server)
+            .set_token_lifetime_policy(FixedAge(Duration::days(7)))
+            .use_save_file(&save_file)
+            .unwrap(),
+    );
+    assert!(!consumer.is_fully_authed());
+}
@@ -0,0 +1,106 @@
+use chrono::{Duration, Utc};
+use tempfile::TempDir;
+
+use crate::consumer::test_cases::{
+    access_token_response, build, saved_token, unauthed_builder, ACCESS_TOKEN, TOKEN_SECRET,
+};
+use crate::test_server::{CannedResponse, TestServer};
+use crate::token_store::{EncryptedFileStore, MemoryStore, Passphrase, SavedToken, TokenStore};
+
+#[test]
+fn access_token_is_stored() {
+    let server = TestServer::start(vec![
+        CannedResponse::ok("oauth_token=hh5s93j4hdidpola&oauth_token_secret=hdhd0244k9j7ao03"),
+        access_token_response(),
+    ]);
+    let store = MemoryStore::new();
+    let consumer = build(unauthed_builder(&server).use_token_store(store.clone()));
+
+    consumer.retrieve_request_token().unwrap();
+    consumer.set_verification_code("hfdp7dh39dks9884").unwrap();
+    consumer.retrieve_access_token().unwrap();
+
+    let saved = store.load().unwrap().unwrap();
+    assert_eq!(ACCESS_TOKEN, saved.access_token);
+    assert_eq!(TOKEN_SECRET, saved.token_secret);
+    assert!(Utc::now() - saved.issued < Duration::minutes(1));
+}
+
+#[test]
+fn stored_token_is_used() {
+    let now = Utc::now();
+    let store = MemoryStore::with_token(SavedToken {
+        issued: now - Duration::minutes(10),
+        last_used: now - Duration::minutes(5),
+        ..saved_token()
+    });
+    let server = TestServer::start(vec![CannedResponse::ok("photos")]);
+    let consumer = build(unauthed_builder(&server).use_token_store(store.clone()));
+    assert!(consumer.is_fully_authed());
+
+    assert_eq!("photos", consumer.get(&server.url("/photos")).unwrap());
+    assert!(Utc::now() - store.load().unwrap().unwrap().last_used < Duration::minutes(1));
+}
+
+#[test]
+fn uses_are_saved_at_most_once_a_minute() {
+    let now = Utc::now();
+    let store = MemoryStore::with_token(SavedToken {
+        issued: now - Duration::minutes(10),
+        last_used: now - Duration::minutes(5),
+        ..saved_token()
+    });
+    let server = TestServer::start(vec![CannedResponse::ok("one"), CannedResponse::ok("two")]);
+    let consumer = build(unauthed_builder(&server).use_token_store(store.clone()));
+
+    consumer.get(&server.url("/photos")).unwrap();
+    let first_save = store.load().unwrap().unwrap();
+    assert!(Utc::now() - first_save.last_used < Duration::minutes(1));
+    consumer.get(&server.url("/photos")).unwrap();
+    assert_eq!(first_save, store.load().unwrap().unwrap());
+}
+
+#[test]
+fn encrypted_save_file() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("save.toml");
+    let passphrase = || Passphrase::callback(|| Ok("correct horse".to_string()));
+    let now = Utc::now();
+    EncryptedFileStore::new(&path, passphrase())
+        .unwrap()
+        .save(&SavedToken {
+            issued: now,
+            last_used: now,
+            ..saved_token()
+        })
+        .unwrap();
+    let server = TestServer::start(vec![]);
+
+    let consumer = build(
+        unauthed_builder(&server)
+            .use_encrypted_save_file(&path, passphrase())
+            .unwrap(),
+    );
+    assert!(consumer.is_fully_authed());
+}
+
+#[test]
+fn token_saved_by_another_process_is_used() {
+    let server = TestServer::start(vec![CannedResponse::ok("photos")]);
+    let store = MemoryStore::new();
+    let consumer = build(unauthed_builder(&server).use_token_store(store.clone()));
+    assert!(!consumer.is_fully_authed());
+
+    let now = Utc::now();
+    store
+        .save(&SavedToken {
+            issued: now,
+            last_used: now,
+            ..saved_token()
+        })
+        .unwrap();
+
+    // No request token is fetched, since the saved token is picked up first.
+    assert_eq!("photos", consumer.get(&server.url("/photos")).unwrap());
+    assert_eq!("/photos", server.requests()[0].url.path());
+}
@@ -0,0 +1,91 @@
+use reqwest::Method;
+
+use crate::consumer::test_cases::{authed_consumer, signature_is_valid, url_with_query};
+use crate::parameters::ParamPair;
+use crate::test_server::{CannedResponse, TestServer};
+
+#[test]
+fn get() {
+    let server = TestServer::start(vec![CannedResponse::ok("got it")]);
+    let consumer = authed_consumer(&server);
+
+    let url = url_with_query(&server, "/photos", "file=vacation.jpg&size=original");
+    assert_eq!("got it", consumer.get(&url).unwrap());
+
+    let requests = server.requests();
+    assert_eq!("GET", requests[0].method);
+    assert_eq!(url, requests[0].url);
+    assert!(signature_is_valid(&requests[0], &[]));
+}
+
+#[test]
+fn form_verbs() {
+    let server = TestServer::start(vec![
+        CannedResponse::ok("posted"),
+        CannedResponse::ok("put"),
+        CannedResponse::ok("patched"),
+    ]);
+    let consumer = authed_consumer(&server);
+    let url = server.url("/orders");
+    let params = [("symbol", "IBM"), ("note", "buy & hold")];
+
+    assert_eq!("posted", consumer.post_form(&url, params).unwrap());
+    assert_eq!("put", consumer.put_form(&url, params).unwrap());
+    assert_eq!("patched", consumer.patch_form(&url, params).unwrap());
+
+    let body_params = params.map(|(name, value)| ParamPair::pair(name, value));
+    let requests = server.requests();
+    for (request, method) in requests.iter().zip(["POST", "PUT", "PATCH"]) {
+        assert_eq!(method, request.method);
+        assert_eq!(
+            Some("application/x-www-form-urlencoded"),
+            request.header("content-type")
+        );
+        assert_eq!(b"symbol=IBM&note=buy%20%26%20hold".to_vec(), request.body);
+        assert!(signature_is_valid(request, &body_params));
+    }
+}
+
+#[test]
+fn delete_and_head() {
+    let server = TestServer::start(vec![
+        CannedResponse::ok("deleted"),
+        CannedResponse::ok("").header("X-Order-Count", "3"),
+    ]);
+    let consumer = authed_consumer(&server);
+    let url = url_with_query(&server, "/orders", "id=88");
+
+    assert_eq!("deleted", consumer.delete(&url).unwrap());
+    let headers = consumer.head(&url).unwrap();
+    assert_eq!("3", headers.get("x-order-count").unwrap());
+
+    let requests = server.requests();
+    assert_eq!("DELETE", requests[0].method);
+    assert_eq!("HEAD", requests[1].method);
+    for request in &requests {
+        assert!(request.body.is_empty());
+        assert!(request.header("content-type").is_none());
+        assert!(signature_is_valid(request, &[]));
+    }
+}
+
+#[test]
+fn send_form_with_any_method() {
+    let server = TestServer::start(vec![CannedResponse::ok("options")]);
+    let consumer = authed_consumer(&server);
+
+    let url = server.url("/orders");
+    assert_eq!(
+        "options",
+        consumer
+            .send_form(Method::OPTIONS, &url, [("a", "1")])
+            .unwrap()
+    );
+
+    let requests = server.requests();
+    assert_eq!("OPTIONS", requests[0].method);
+    assert!(signature_is_valid(
+        &requests[0],
+        &[ParamPair::pair("a", "1")]
+    ));
+}
@@ -1,21 +1,66 @@
 use thiserror::Error;
 
+use crate::problem::{OauthProblem, ProblemReport};
+
 /// Error type for the OAgain library.
 #[derive(Error, Debug)]
 pub enum OagainError {
+    #[error("The config file is invalid: {0}")]
+    BadConfig(String),
+
+    #[error("The environment variable {0} has a bad value.")]
+    BadEnvVar(String),
+
+    #[error("A bad header was added to a request: {0}")]
+    BadHeader(String),
+
+    #[error("The RSA private key could not be read: {0}")]
+    BadRsaKey(String),
+
+    #[error("The token response from the server could not be parsed.")]
+    BadTokenResponse,
+
     // TODO: I would like to figure out how to include the underlying error in this.
     #[error("A bad URL was passed to Consumer Builder")]
     BadUrl,
 
+    #[error("The authorization callback failed: {0}")]
+    CallbackError(String),
+
+    #[error("The authorization callback was for a different request token.")]
+    CallbackTokenMismatch,
+
     #[error("A parse error occurred in a date string.")]
     ChronoError(#[from] chrono::ParseError),
 
     #[error("A param starting with 'oauth_' is disallowed, {0}")]
     DisallowedOauthParam(String),
 
+    #[error("The save file could not be encrypted or decrypted: {0}")]
+    EncryptionError(String),
+
+    #[error("The server responded with HTTP status {status}")]
+    HttpStatus {
+        status: reqwest::StatusCode,
+        headers: Box<reqwest::header::HeaderMap>,
+        body: String,
+    },
+
+    #[error("In profile {profile}: {source}")]
+    InProfile {
+        profile: String,
+        source: Box<OagainError>,
+    },
+
+    #[error("PLAINTEXT signatures may only be sent over https, not to {0}")]
+    InsecurePlainText(String),
+
     #[error("An IO error occurred: {0}")]
     IoError(#[from] std::io::Error),
 
+    #[error("A JSON error occurred: {0}")]
+    JsonError(#[from] serde_json::Error),
+
     #[error("The access token was missing from the server response")]
     MissingAccessToken,
 
@@ -34,15 +79,30 @@ pub enum OagainError {
     #[error("The consumer token was not found ({0}).")]
     MissingConsumerToken(&'static str),
 
+    #[error("The environment variable {0} must also be set.")]
+    MissingEnvVar(String),
+
+    #[error("The save file passphrase was not found in the environment variable {0}.")]
+    MissingPassphrase(String),
+
     #[error("A required path is missing from a URL, {0}.")]
     MissingPath(String),
 
+    #[error("The profile {0} was not found.")]
+    MissingProfile(String),
+
+    #[error("The renew access token url is missing.")]
+    MissingRenewAccessTokenUrl,
+
     #[error("The request token is missing. Perhaps you're calling stuff out of order.")]
     MissingRequestToken,
 
     #[error("The request token url is missing.")]
     MissingRequestTokenUrl,
 
+    #[error("The revoke access token url is missing.")]
+    MissingRevokeAccessTokenUrl,
+
     #[error("A required scheme is missing from a URL, {0}.")]
     MissingScheme(String),
 
@@ -52,15 +112,30 @@ pub enum OagainError {
     #[error("A required token secret was not found")]
     MissingTokenSecret,
 
-    // TODO: Ideally, we should try to renew it if it's eligible.
-    //       (See the [docs](https://apisb.etrade.com/docs/api/authorization/renew_access_token.html).
-    // TODO: Also, we should save the _last use_ time, not the save time.
+    #[error("The user needs to authorize at {0}")]
+    NeedsUserAuth(url::Url),
+
+    #[error("The server reported an OAuth problem ({}) with HTTP status {status}", .report.problem)]
+    OauthProblem {
+        status: reqwest::StatusCode,
+        report: Box<ProblemReport>,
+    },
+
     #[error("The token was too old to use.")]
     OldToken,
 
+    #[error("The {0} can't hold profiles.")]
+    ProfileUnsupported(&'static str),
+
     #[error("A reqwest error")]
     ReqwestError(#[from] reqwest::Error),
 
+    #[error("An RSA error occurred: {0}")]
+    RsaError(#[from] rsa::Error),
+
+    #[error("A streaming request body can't be signed.")]
+    StreamingBody,
+
     #[error("An error occurred while reading the toml file: {0}")]
     TomlError(#[from] toml::de::Error),
 
@@ -71,5 +146,16 @@ pub enum OagainError {
     Utf3Error(#[from] std::string::FromUtf8Error),
 }
 
+impl OagainError {
+    /// The reported OAuth problem, if this error has one. Useful for deciding whether to retry.
+    pub fn oauth_problem(&self) -> Option<&OauthProblem> {
+        match self {
+            OagainError::OauthProblem { report, .. } => Some(&report.problem),
+            OagainError::InProfile { source, .. } => source.oauth_problem(),
+            _ => None,
+        }
+    }
+}
+
 /// Result type for the OAgain library.
 pub type Result<T> = std::result::Result<T, OagainError>;
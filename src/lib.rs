@@ -4,12 +4,33 @@ mod error;
 mod nonce_provider;
 mod parameters;
 mod pencoding;
+mod problem;
+mod profile;
 mod signing;
+mod token_lifetime;
+mod token_store;
 mod util;
 
 #[cfg(test)]
 mod test_constants;
+#[cfg(test)]
+mod test_server;
 
 pub use consumer::ETradePreset;
-pub use consumer::{BasicConsumer, Builder, Consumer};
+pub use consumer::{
+    AccessToken, Authorized, BasicConsumer, BrowserPrompt, Builder, CallbackServer, Consumer,
+    ConsumerConfig, FlowError, Headless, LoopbackCallback, PendingAuthorization, RequestTokenHeld,
+    SignedRequestBuilder, SignedResponse, TerminalPrompt, Unauthorized, UserAuthorizer, Verified,
+};
 pub use error::{OagainError, Result};
+pub use problem::{OauthProblem, ProblemReport};
+pub use reqwest::header::HeaderMap;
+pub use reqwest::{Method, StatusCode};
+pub use signing::{HmacSha1, HmacSha256, HmacSha512, PlainText, RsaSha1, SignatureMethod};
+pub use token_lifetime::{
+    DailyCutoff, FixedAge, IdleTimeout, NeverExpires, TokenLifetimePolicy, TokenStatus,
+};
+pub use token_store::{
+    DirectoryStore, EncryptedFileStore, JsonFileStore, KeyedMemoryStore, KeyedTokenStore,
+    MemoryStore, Passphrase, SavedToken, TokenStore, TokenStoreLock, TomlFileStore,
+};
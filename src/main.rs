@@ -8,7 +8,7 @@ use oagain::{BasicConsumer, ETradePreset};
 fn main() -> Result<()> {
     env_logger::init();
 
-    let mut consumer = BasicConsumer::builder()
+    let consumer = BasicConsumer::builder()
         .use_preset(ETradePreset)?
         .use_secrets_file("secrets.toml")?
         .use_save_file("save.toml")?
@@ -2,12 +2,20 @@ use crate::error::Result;
 use crate::nonce_provider::epoch_provider::{EpochProvider, SystemEpochProvider};
 use crate::nonce_provider::NonceProvider;
 use std::collections::HashSet;
+use std::sync::{Mutex, PoisonError};
 
 // TODO: you could add a nonce_generator to make the nonce value random.
 
 #[derive(Debug)]
 pub struct BasicNonce<EP: EpochProvider> {
     epoch_provider: EP,
+    used: Mutex<UsedNonces>,
+}
+
+// The nonces handed out for the latest timestamp. Behind a lock, so threads sharing a consumer
+// never get the same nonce.
+#[derive(Debug, Default)]
+struct UsedNonces {
     last_timestamp: u32,
     nonces_for_last_timestamp: HashSet<String>,
 }
@@ -22,8 +30,7 @@ impl<EP: EpochProvider> BasicNonce<EP> {
     pub fn new(epoch_provider: EP) -> BasicNonce<EP> {
         BasicNonce {
             epoch_provider,
-            last_timestamp: Default::default(),
-            nonces_for_last_timestamp: Default::default(),
+            used: Default::default(),
         }
     }
 
@@ -34,26 +41,28 @@ impl<EP: EpochProvider> BasicNonce<EP> {
 }
 
 impl<EP: EpochProvider> NonceProvider for BasicNonce<EP> {
-    fn nonce(&mut self) -> Result<(u32, String)> {
+    fn nonce(&self) -> Result<(u32, String)> {
         // let now = std::time::SystemTime::now();
         // let epoch_duration = now.duration_since(UNIX_EPOCH)?;
         //
         // // Should fit. At least until 2033
         // let epoch_millis = epoch_duration.as_millis() as u32;
+        let mut used = self.used.lock().unwrap_or_else(PoisonError::into_inner);
+        // Read under the lock, so the timestamps of successive nonces never go backwards.
         let epoch_millis = self.epoch_provider.epoch();
-        if epoch_millis == self.last_timestamp {
+        if epoch_millis == used.last_timestamp {
             let nonce = format!(
                 "nonce-{}-{}",
                 epoch_millis,
-                self.nonces_for_last_timestamp.len()
+                used.nonces_for_last_timestamp.len()
             );
-            self.nonces_for_last_timestamp.insert(nonce.clone());
+            used.nonces_for_last_timestamp.insert(nonce.clone());
             Ok((epoch_millis, nonce))
         } else {
-            self.nonces_for_last_timestamp.clear();
-            self.last_timestamp = epoch_millis;
+            used.nonces_for_last_timestamp.clear();
+            used.last_timestamp = epoch_millis;
             let nonce = format!("nonce-{}-0", epoch_millis);
-            self.nonces_for_last_timestamp.insert(nonce.clone());
+            used.nonces_for_last_timestamp.insert(nonce.clone());
             Ok((epoch_millis, nonce))
         }
     }
@@ -61,6 +70,10 @@ impl<EP: EpochProvider> NonceProvider for BasicNonce<EP> {
 
 #[cfg(test)]
 mod test {
+    use std::collections::HashSet;
+    use std::sync::Arc;
+    use std::thread;
+
     use crate::nonce_provider::basic::BasicNonce;
     use crate::nonce_provider::epoch_provider::test_provider::TestEpochProvider;
     use crate::nonce_provider::NonceProvider;
@@ -97,4 +110,25 @@ mod test {
             nonce_provider.nonce().unwrap()
         );
     }
+
+    #[test]
+    fn nonces_are_unique_across_threads() {
+        let nonce_provider = Arc::new(BasicNonce::new(TestEpochProvider::new(44)));
+        let handles = (0..8)
+            .map(|_| {
+                let nonce_provider = Arc::clone(&nonce_provider);
+                thread::spawn(move || {
+                    (0..500)
+                        .map(|_| nonce_provider.nonce().unwrap())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let nonces = handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect::<HashSet<_>>();
+        assert_eq!(8 * 500, nonces.len());
+    }
 }
@@ -4,7 +4,7 @@ use std::time::UNIX_EPOCH;
 #[cfg(test)]
 pub mod test_provider;
 
-pub trait EpochProvider: Debug {
+pub trait EpochProvider: Debug + Send + Sync {
     fn epoch(&self) -> u32;
 }
 
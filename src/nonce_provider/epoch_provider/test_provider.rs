@@ -1,5 +1,6 @@
 use crate::nonce_provider::epoch_provider::EpochProvider;
 
+#[derive(Debug)]
 pub struct TestEpochProvider(u32);
 
 impl TestEpochProvider {
@@ -4,13 +4,15 @@ mod epoch_provider;
 use crate::error::Result;
 use std::fmt::Debug;
 
-pub trait NonceProvider: Debug {
-    // Return a (timestamp, nonce) pair.
-    fn nonce(&mut self) -> Result<(u32, String)>;
+/// Supplies the timestamp and nonce for each request. Takes `&self`, since a consumer shared
+/// between threads signs concurrently, so implementations must keep any state behind a lock.
+pub trait NonceProvider: Debug + Send + Sync {
+    // Return a (timestamp, nonce) pair. The pair must never be repeated.
+    fn nonce(&self) -> Result<(u32, String)>;
 }
 
 pub use basic::BasicNonce;
-pub use epoch_provider::{EpochProvider, SystemEpochProvider};
+pub use epoch_provider::SystemEpochProvider;
 
 #[cfg(test)]
 pub use epoch_provider::test_provider::TestEpochProvider;
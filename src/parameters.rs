@@ -1,4 +1,6 @@
-use crate::pencoding::{decode_str, encode_param};
+use std::fmt::{Display, Formatter};
+
+use crate::pencoding::{decode_str, encode_param, try_decode_str};
 
 #[derive(Debug, Eq, PartialEq, PartialOrd, Ord, Clone)]
 pub struct ParamPair {
@@ -31,9 +33,10 @@ impl ParamPair {
     }
 }
 
-impl ToString for ParamPair {
-    fn to_string(&self) -> String {
-        format!(
+impl Display for ParamPair {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
             "{}={}",
             encode_param(&self.name),
             self.value.as_ref().map(encode_param).unwrap_or_default()
@@ -62,13 +65,51 @@ impl From<&str> for ParamPair {
     }
 }
 
-pub fn decode_params_string(s: impl AsRef<str>) -> Vec<ParamPair> {
-    s.as_ref().split('&').map(ParamPair::from).collect()
+/// Decodes a form-encoded params string. Returns None on malformed input, since it usually came
+/// from a server.
+pub fn try_decode_params_string(s: impl AsRef<str>) -> Option<Vec<ParamPair>> {
+    s.as_ref()
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            None => Some(ParamPair {
+                name: try_decode_str(pair)?,
+                value: None,
+            }),
+            Some((name, value)) => Some(ParamPair::pair(
+                try_decode_str(name)?,
+                try_decode_str(value)?,
+            )),
+        })
+        .collect()
+}
+
+/// Decodes the params from an `OAuth` auth header, such as `Authorization` or
+/// `WWW-Authenticate`. Returns None if the header doesn't use the `OAuth` scheme, or is malformed.
+/// (Spec 3.5.1)
+pub fn try_decode_auth_header(header: impl AsRef<str>) -> Option<Vec<ParamPair>> {
+    let header = header.as_ref().trim();
+    let (scheme, params) = header.split_once(' ').unwrap_or((header, ""));
+    if !scheme.eq_ignore_ascii_case("OAuth") {
+        return None;
+    }
+    params
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=')?;
+            let value = value.trim().trim_matches('"');
+            Some(ParamPair::pair(
+                try_decode_str(name.trim())?,
+                try_decode_str(value)?,
+            ))
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod test {
-    use crate::parameters::{decode_params_string, ParamPair};
+    use crate::parameters::{try_decode_auth_header, try_decode_params_string, ParamPair};
 
     #[test]
     fn basic_test() {
@@ -87,9 +128,10 @@ mod test {
                     value: None
                 }
             ],
-            decode_params_string(
+            try_decode_params_string(
                 "oauth_token=ab3cd9j4ks73hf7g&oauth_token_secret=xyz4992k83j47x0b&dummy_param"
             )
+            .unwrap()
         )
     }
 
@@ -106,7 +148,7 @@ mod test {
                     value: None
                 }
             ],
-            decode_params_string("foo%20bar=quux&%C2%A1Andale%21")
+            try_decode_params_string("foo%20bar=quux&%C2%A1Andale%21").unwrap()
         );
     }
 
@@ -132,4 +174,30 @@ mod test {
             .to_string()
         )
     }
+
+    #[test]
+    fn try_decoding_test() {
+        assert_eq!(
+            Some(vec![ParamPair::pair("a", "1=2"), ParamPair::single("b"),]),
+            try_decode_params_string("a=1=2&b")
+        );
+        assert_eq!(None, try_decode_params_string("a=100%"));
+    }
+
+    #[test]
+    fn auth_header_test() {
+        assert_eq!(
+            Some(vec![
+                ParamPair::pair("realm", "Photos"),
+                ParamPair::pair("oauth_problem", "token_expired"),
+                ParamPair::pair("oauth_problem_advice", "log in again"),
+            ]),
+            try_decode_auth_header(
+                "OAuth realm=\"Photos\",oauth_problem=\"token_expired\", \
+                 oauth_problem_advice=\"log%20in%20again\""
+            )
+        );
+        assert_eq!(Some(vec![]), try_decode_auth_header("OAuth"));
+        assert_eq!(None, try_decode_auth_header("Basic realm=\"Photos\""));
+    }
 }
@@ -22,23 +22,31 @@ pub fn encode_param(s: impl AsRef<str>) -> String {
 }
 
 pub fn decode_str(s: impl AsRef<str>) -> String {
+    // TODO: get rid of this unwrap
+    try_decode_str(s).unwrap()
+}
+
+/// Like `decode_str()`, but returns None for bad escapes or invalid UTF-8 instead of panicking.
+/// Use this for input that didn't come from us, like error responses.
+pub fn try_decode_str(s: impl AsRef<str>) -> Option<String> {
     let mut bytes: Vec<u8> = vec![];
     let mut input_bytes = s.as_ref().as_bytes();
-    while !input_bytes.is_empty() {
-        // unwrap: tail isn't empty, so there will always be a first byte
-        let byte = input_bytes.first().unwrap();
+    while let Some(byte) = input_bytes.first() {
         if *byte != b'%' {
             bytes.push(*byte);
             input_bytes = &input_bytes[1..];
         } else {
-            let nibble1 = hex_nibble(input_bytes[1] as char);
-            let nibble2 = hex_nibble(input_bytes[2] as char);
+            let escape = input_bytes.get(1..3)?;
+            if !escape.iter().all(u8::is_ascii_hexdigit) {
+                return None;
+            }
+            let nibble1 = hex_nibble(escape[0] as char);
+            let nibble2 = hex_nibble(escape[1] as char);
             bytes.push(nibble1 * 16 + nibble2);
             input_bytes = &input_bytes[3..];
         }
     }
-    // TODO: get rid of this unwrap
-    String::from_utf8(bytes).unwrap()
+    String::from_utf8(bytes).ok()
 }
 
 fn hex_nibble(ch: char) -> u8 {
@@ -56,7 +64,7 @@ fn hex_nibble(ch: char) -> u8 {
 
 #[cfg(test)]
 mod test {
-    use crate::pencoding::{encode_param, hex_nibble};
+    use crate::pencoding::{encode_param, hex_nibble, try_decode_str};
 
     #[test]
     fn no_escapes() {
@@ -90,4 +98,12 @@ mod test {
         assert_eq!(12, hex_nibble('C'));
         assert_eq!(15, hex_nibble('F'));
     }
+
+    #[test]
+    fn try_decode() {
+        assert_eq!(Some("a b".to_string()), try_decode_str("a%20b"));
+        assert_eq!(None, try_decode_str("100%"));
+        assert_eq!(None, try_decode_str("%zz"));
+        assert_eq!(None, try_decode_str("%FF"));
+    }
 }
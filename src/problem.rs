@@ -0,0 +1,247 @@
+//! OAuth Problem Reporting, which providers use to explain why a request was refused.
+//! (See the [extension](https://wiki.oauth.net/w/page/12238543/ProblemReporting).)
+
+use std::fmt::{Display, Formatter};
+
+use reqwest::header::{HeaderMap, WWW_AUTHENTICATE};
+
+use crate::constants::{
+    OAUTH_ACCEPTABLE_TIMESTAMPS_PARAM_NAME, OAUTH_ACCEPTABLE_VERSIONS_PARAM_NAME,
+    OAUTH_PARAMETERS_ABSENT_PARAM_NAME, OAUTH_PARAMETERS_REJECTED_PARAM_NAME,
+    OAUTH_PROBLEM_ADVICE_PARAM_NAME, OAUTH_PROBLEM_PARAM_NAME,
+};
+use crate::parameters::{try_decode_auth_header, try_decode_params_string, ParamPair};
+
+/// The value of the `oauth_problem` param.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum OauthProblem {
+    VersionRejected,
+    ParameterAbsent,
+    ParameterRejected,
+    TimestampRefused,
+    NonceUsed,
+    SignatureMethodRejected,
+    SignatureInvalid,
+    ConsumerKeyUnknown,
+    ConsumerKeyRejected,
+    ConsumerKeyRefused,
+    TokenUsed,
+    TokenExpired,
+    TokenRevoked,
+    TokenRejected,
+    AdditionalAuthorizationRequired,
+    PermissionUnknown,
+    PermissionDenied,
+    UserRefused,
+    /// A problem that isn't in the extension.
+    Other(String),
+}
+
+impl OauthProblem {
+    pub fn as_str(&self) -> &str {
+        match self {
+            OauthProblem::VersionRejected => "version_rejected",
+            OauthProblem::ParameterAbsent => "parameter_absent",
+            OauthProblem::ParameterRejected => "parameter_rejected",
+            OauthProblem::TimestampRefused => "timestamp_refused",
+            OauthProblem::NonceUsed => "nonce_used",
+            OauthProblem::SignatureMethodRejected => "signature_method_rejected",
+            OauthProblem::SignatureInvalid => "signature_invalid",
+            OauthProblem::ConsumerKeyUnknown => "consumer_key_unknown",
+            OauthProblem::ConsumerKeyRejected => "consumer_key_rejected",
+            OauthProblem::ConsumerKeyRefused => "consumer_key_refused",
+            OauthProblem::TokenUsed => "token_used",
+            OauthProblem::TokenExpired => "token_expired",
+            OauthProblem::TokenRevoked => "token_revoked",
+            OauthProblem::TokenRejected => "token_rejected",
+            OauthProblem::AdditionalAuthorizationRequired => "additional_authorization_required",
+            OauthProblem::PermissionUnknown => "permission_unknown",
+            OauthProblem::PermissionDenied => "permission_denied",
+            OauthProblem::UserRefused => "user_refused",
+            OauthProblem::Other(problem) => problem,
+        }
+    }
+}
+
+impl From<&str> for OauthProblem {
+    fn from(value: &str) -> Self {
+        match value {
+            "version_rejected" => OauthProblem::VersionRejected,
+            "parameter_absent" => OauthProblem::ParameterAbsent,
+            "parameter_rejected" => OauthProblem::ParameterRejected,
+            "timestamp_refused" => OauthProblem::TimestampRefused,
+            "nonce_used" => OauthProblem::NonceUsed,
+            "signature_method_rejected" => OauthProblem::SignatureMethodRejected,
+            "signature_invalid" => OauthProblem::SignatureInvalid,
+            "consumer_key_unknown" => OauthProblem::ConsumerKeyUnknown,
+            "consumer_key_rejected" => OauthProblem::ConsumerKeyRejected,
+            "consumer_key_refused" => OauthProblem::ConsumerKeyRefused,
+            "token_used" => OauthProblem::TokenUsed,
+            "token_expired" => OauthProblem::TokenExpired,
+            "token_revoked" => OauthProblem::TokenRevoked,
+            "token_rejected" => OauthProblem::TokenRejected,
+            "additional_authorization_required" => OauthProblem::AdditionalAuthorizationRequired,
+            "permission_unknown" => OauthProblem::PermissionUnknown,
+            "permission_denied" => OauthProblem::PermissionDenied,
+            "user_refused" => OauthProblem::UserRefused,
+            other => OauthProblem::Other(other.to_string()),
+        }
+    }
+}
+
+impl Display for OauthProblem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A reported problem, along with the extra params that explain it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProblemReport {
+    pub problem: OauthProblem,
+    /// From `oauth_acceptable_versions`, the (lowest, highest) versions the provider accepts.
+    pub acceptable_versions: Option<(String, String)>,
+    /// From `oauth_acceptable_timestamps`, the (earliest, latest) timestamps the provider accepts.
+    pub acceptable_timestamps: Option<(u64, u64)>,
+    /// From `oauth_parameters_absent`, the names of the params that were missing.
+    pub parameters_absent: Vec<String>,
+    /// From `oauth_parameters_rejected`, the names of the params that were refused.
+    pub parameters_rejected: Vec<String>,
+    /// From `oauth_problem_advice`, a human-readable explanation.
+    pub advice: Option<String>,
+}
+
+impl ProblemReport {
+    /// Finds a problem report in a response, looking first in the `WWW-Authenticate` header,
+    /// then in a form-encoded body. Returns None if there is no `oauth_problem` param in either.
+    pub(crate) fn from_response(headers: &HeaderMap, body: &[u8]) -> Option<ProblemReport> {
+        headers
+            .get_all(WWW_AUTHENTICATE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .filter_map(try_decode_auth_header)
+            .find_map(|params| ProblemReport::from_params(&params))
+            .or_else(|| {
+                let body = std::str::from_utf8(body).ok()?;
+                ProblemReport::from_params(&try_decode_params_string(body.trim())?)
+            })
+    }
+
+    pub(crate) fn from_params(params: &[ParamPair]) -> Option<ProblemReport> {
+        let find = |name: &str| {
+            params
+                .iter()
+                .find(|param| param.name == name)
+                .and_then(|param| param.value.as_deref())
+        };
+        let range = |name: &str| {
+            find(name)
+                .and_then(|value| value.split_once('-'))
+                .map(|(low, high)| (low.to_string(), high.to_string()))
+        };
+        let names = |name: &str| {
+            find(name)
+                .map(|value| {
+                    value
+                        .split('&')
+                        .filter(|name| !name.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        Some(ProblemReport {
+            problem: OauthProblem::from(find(OAUTH_PROBLEM_PARAM_NAME)?),
+            acceptable_versions: range(OAUTH_ACCEPTABLE_VERSIONS_PARAM_NAME),
+            acceptable_timestamps: range(OAUTH_ACCEPTABLE_TIMESTAMPS_PARAM_NAME)
+                .and_then(|(low, high)| Some((low.parse().ok()?, high.parse().ok()?))),
+            parameters_absent: names(OAUTH_PARAMETERS_ABSENT_PARAM_NAME),
+            parameters_rejected: names(OAUTH_PARAMETERS_REJECTED_PARAM_NAME),
+            advice: find(OAUTH_PROBLEM_ADVICE_PARAM_NAME).map(str::to_string),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use reqwest::header::{HeaderMap, HeaderValue, WWW_AUTHENTICATE};
+
+    use crate::problem::{OauthProblem, ProblemReport};
+
+    #[test]
+    fn problem_names() {
+        for name in [
+            "nonce_used",
+            "token_expired",
+            "additional_authorization_required",
+        ] {
+            assert_eq!(name, OauthProblem::from(name).as_str());
+        }
+        assert_eq!(
+            OauthProblem::Other("too_many_requests".to_string()),
+            OauthProblem::from("too_many_requests")
+        );
+    }
+
+    #[test]
+    fn from_body() {
+        let report = ProblemReport::from_response(
+            &HeaderMap::new(),
+            b"oauth_problem=timestamp_refused&oauth_acceptable_timestamps=1191242000-1191242200",
+        )
+        .unwrap();
+        assert_eq!(OauthProblem::TimestampRefused, report.problem);
+        assert_eq!(Some((1191242000, 1191242200)), report.acceptable_timestamps);
+        assert_eq!(None, report.advice);
+    }
+
+    #[test]
+    fn from_body_with_lists() {
+        let report = ProblemReport::from_response(
+            &HeaderMap::new(),
+            b"oauth_problem=parameter_absent&oauth_parameters_absent=oauth_nonce%26oauth_timestamp\
+              &oauth_problem_advice=Send%20a%20nonce.",
+        )
+        .unwrap();
+        assert_eq!(OauthProblem::ParameterAbsent, report.problem);
+        assert_eq!(
+            vec!["oauth_nonce", "oauth_timestamp"],
+            report.parameters_absent
+        );
+        assert_eq!(Some("Send a nonce."), report.advice.as_deref());
+    }
+
+    #[test]
+    fn from_header() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            WWW_AUTHENTICATE,
+            HeaderValue::from_static(
+                "OAuth realm=\"photos\", oauth_problem=\"version_rejected\", \
+                 oauth_acceptable_versions=\"1.0-1.0\"",
+            ),
+        );
+        let report = ProblemReport::from_response(&headers, b"<html>nope</html>").unwrap();
+        assert_eq!(OauthProblem::VersionRejected, report.problem);
+        assert_eq!(
+            Some(("1.0".to_string(), "1.0".to_string())),
+            report.acceptable_versions
+        );
+    }
+
+    #[test]
+    fn no_problem() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            WWW_AUTHENTICATE,
+            HeaderValue::from_static("Basic realm=\"x\""),
+        );
+        assert_eq!(None, ProblemReport::from_response(&headers, b"go away"));
+        assert_eq!(None, ProblemReport::from_response(&headers, b"100%=broken"));
+        assert_eq!(
+            None,
+            ProblemReport::from_response(&headers, b"oauth_token=abc")
+        );
+    }
+}
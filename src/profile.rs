@@ -0,0 +1,78 @@
+//! Named profiles, which let one secrets or save file hold several sets of keys and tokens in
+//! `[profile.name]` tables.
+
+use toml::{Table, Value};
+
+use crate::constants::PROFILE_TABLE_NAME;
+
+/// Describes the profile for log messages, e.g., ` (profile sandbox)`, or nothing without one.
+pub fn profile_label(profile: &Option<String>) -> String {
+    profile
+        .as_ref()
+        .map(|profile| format!(" (profile {})", profile))
+        .unwrap_or_default()
+}
+
+/// Returns the `[profile.name]` table, or the whole table if there's no profile.
+pub fn profile_table<'a>(table: &'a Table, profile: Option<&str>) -> Option<&'a Table> {
+    match profile {
+        None => Some(table),
+        Some(profile) => table
+            .get(PROFILE_TABLE_NAME)
+            .and_then(Value::as_table)
+            .and_then(|profiles| profiles.get(profile))
+            .and_then(Value::as_table),
+    }
+}
+
+/// Returns the `[profile.name]` table, creating it (and replacing anything else with the same
+/// name) if needed.
+pub fn profile_table_mut<'a>(table: &'a mut Table, profile: &str) -> &'a mut Table {
+    let profiles = table_entry(table, PROFILE_TABLE_NAME);
+    table_entry(profiles, profile)
+}
+
+fn table_entry<'a>(table: &'a mut Table, name: &str) -> &'a mut Table {
+    if !table.get(name).is_some_and(Value::is_table) {
+        table.insert(name.to_string(), Value::Table(Table::new()));
+    }
+    // unwrap: it was just made a table.
+    table.get_mut(name).and_then(Value::as_table_mut).unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::profile::{profile_table, profile_table_mut};
+
+    #[test]
+    fn select_profile() {
+        let table = "token = \"top\"\n[profile.sandbox]\ntoken = \"sb\"\n"
+            .parse::<toml::Table>()
+            .unwrap();
+        assert_eq!(
+            "top",
+            profile_table(&table, None).unwrap()["token"]
+                .as_str()
+                .unwrap()
+        );
+        assert_eq!(
+            "sb",
+            profile_table(&table, Some("sandbox")).unwrap()["token"]
+                .as_str()
+                .unwrap()
+        );
+        assert!(profile_table(&table, Some("production")).is_none());
+    }
+
+    #[test]
+    fn create_profile() {
+        let mut table = "profile = 3".parse::<toml::Table>().unwrap();
+        profile_table_mut(&mut table, "sandbox").insert("token".to_string(), "sb".into());
+        assert_eq!(
+            "sb",
+            profile_table(&table, Some("sandbox")).unwrap()["token"]
+                .as_str()
+                .unwrap()
+        );
+    }
+}
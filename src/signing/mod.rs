@@ -4,18 +4,49 @@ mod test_cases;
 mod signature_base_string;
 mod signer;
 
+use crate::error::Result;
 use crate::pencoding::encode_param;
 use base64::Engine;
-use crypto::mac::Mac;
+use hmac::Hmac;
+use rsa::{Pkcs1v15Sign, RsaPrivateKey};
+use sha1::{Digest, Sha1};
+use sha2::{Sha256, Sha512};
 pub use signature_base_string::concat_request_elements;
+pub use signer::{HmacSha1, HmacSha256, HmacSha512, PlainText, RsaSha1, SignatureMethod};
 
 pub fn make_signing_key(consumer_secret: impl AsRef<str>, token: impl AsRef<str>) -> String {
     format!("{}&{}", encode_param(consumer_secret), encode_param(token))
 }
 
 pub fn sign_string_hmac(key: impl AsRef<str>, text: impl AsRef<str>) -> String {
-    use crypto::{hmac, sha1};
-    let mut hmac = hmac::Hmac::new(sha1::Sha1::new(), key.as_ref().as_bytes());
-    hmac.input(text.as_ref().as_bytes());
-    base64::engine::general_purpose::STANDARD.encode(hmac.result().code())
+    sign_string_hmac_with::<Hmac<Sha1>>(key, text)
+}
+
+pub fn sign_string_hmac_sha256(key: impl AsRef<str>, text: impl AsRef<str>) -> String {
+    sign_string_hmac_with::<Hmac<Sha256>>(key, text)
+}
+
+pub fn sign_string_hmac_sha512(key: impl AsRef<str>, text: impl AsRef<str>) -> String {
+    sign_string_hmac_with::<Hmac<Sha512>>(key, text)
+}
+
+fn sign_string_hmac_with<M: hmac::Mac + hmac::digest::KeyInit>(
+    key: impl AsRef<str>,
+    text: impl AsRef<str>,
+) -> String {
+    // unwrap: HMAC accepts keys of any length.
+    let mut hmac = <M as hmac::Mac>::new_from_slice(key.as_ref().as_bytes()).unwrap();
+    hmac::Mac::update(&mut hmac, text.as_ref().as_bytes());
+    base64::engine::general_purpose::STANDARD.encode(hmac::Mac::finalize(hmac).into_bytes())
+}
+
+/// Returns the base64-encoded `hash` of `body`, for the `oauth_body_hash` param.
+pub fn hash_body<D: Digest>(body: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(D::digest(body))
+}
+
+pub fn sign_string_rsa(key: &RsaPrivateKey, text: impl AsRef<str>) -> Result<String> {
+    let digest = Sha1::digest(text.as_ref().as_bytes());
+    let signature = key.sign(Pkcs1v15Sign::new::<Sha1>(), &digest)?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(signature))
 }
@@ -17,11 +17,18 @@ fn construct_request_url(url: &Url) -> String {
 }
 
 // pairs of (name: &str, value: &str)
+// Pairs are sorted by their _encoded_ names and values. (RFC 5849 3.4.1.3.2)
 pub(crate) fn normalize_request_parameters(pairs: impl Iterator<Item = ParamPair>) -> String {
     pairs
-        .sorted()
         .filter(|p| p.name != OAUTH_SIGNATURE_PARAM_NAME)
-        .map(|pair| pair.to_string())
+        .map(|pair| {
+            (
+                encode_param(&pair.name),
+                pair.value.as_ref().map(encode_param).unwrap_or_default(),
+            )
+        })
+        .sorted()
+        .map(|(name, value)| format!("{}={}", name, value))
         .join("&")
 }
 
@@ -96,6 +103,15 @@ mod test {
         );
     }
 
+    #[test]
+    fn sort_encoded_names() {
+        // '@' sorts after '2', but its encoding, "%40", sorts before it.
+        assert_eq!(
+            "c%40=&c2=",
+            normalize_request_parameters(["c2=".into(), "c%40=".into()].into_iter())
+        );
+    }
+
     #[test]
     fn strip_query_and_fragment() {
         let url = Url::parse("http://www.example.com/has_query?q=cat&ref=home#fragment").unwrap();
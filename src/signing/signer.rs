@@ -0,0 +1,254 @@
+use std::fmt::{Debug, Formatter};
+
+use base64::Engine;
+use rsa::pkcs1::DecodeRsaPrivateKey;
+use rsa::pkcs8::DecodePrivateKey;
+use rsa::{Pkcs1v15Sign, RsaPrivateKey};
+use sha1::{Digest, Sha1};
+use sha2::{Sha256, Sha512};
+use subtle::ConstantTimeEq;
+
+use crate::constants::{
+    OAUTH_SIGNATURE_METHOD_HMAC_SHA256_VALUE, OAUTH_SIGNATURE_METHOD_HMAC_SHA512_VALUE,
+    OAUTH_SIGNATURE_METHOD_HMAC_VALUE, OAUTH_SIGNATURE_METHOD_PLAINTEXT_VALUE,
+    OAUTH_SIGNATURE_METHOD_RSA_VALUE,
+};
+use crate::error::{OagainError, Result};
+use crate::signing::{
+    hash_body, make_signing_key, sign_string_hmac, sign_string_hmac_sha256,
+    sign_string_hmac_sha512, sign_string_rsa,
+};
+
+/// A method for signing requests. (Spec 9)
+///
+/// The built-in methods are [HmacSha1], [HmacSha256], [HmacSha512], [RsaSha1], and [PlainText].
+/// Other methods can be supplied by implementing this trait and passing them to
+/// `Builder::set_signature_method()`.
+pub trait SignatureMethod: Debug + Send + Sync {
+    /// The value sent in the `oauth_signature_method` param.
+    fn name(&self) -> &str;
+
+    /// Returns the signature for `base_string`, which is sent unencoded in `oauth_signature`.
+    fn sign(&self, consumer_secret: &str, token_secret: &str, base_string: &str) -> Result<String>;
+
+    /// Returns true if `signature` is the correct signature for `base_string`. The default
+    /// compares in constant time, so the comparison doesn't reveal how much of a guess was right.
+    fn verify(
+        &self,
+        signature: &str,
+        consumer_secret: &str,
+        token_secret: &str,
+        base_string: &str,
+    ) -> Result<bool> {
+        let expected = self.sign(consumer_secret, token_secret, base_string)?;
+        Ok(expected.as_bytes().ct_eq(signature.as_bytes()).into())
+    }
+
+    /// Returns the `oauth_body_hash` value for `body`, or None if this method doesn't support
+    /// body hashes. The hash algorithm should match the one used for signing. (Body Hash 3.2)
+    fn body_hash(&self, _body: &[u8]) -> Option<String> {
+        None
+    }
+
+    /// Returns true if this method needs the consumer secret to sign requests.
+    fn uses_consumer_secret(&self) -> bool {
+        true
+    }
+
+    /// Returns true if this method signs the signature base string. If false, the base string
+    /// is not constructed, and an empty string is passed to `sign()`.
+    fn uses_base_string(&self) -> bool {
+        true
+    }
+}
+
+/// HMAC-SHA1, keyed with the consumer secret and token secret. (Spec 9.2)
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HmacSha1;
+
+impl SignatureMethod for HmacSha1 {
+    fn name(&self) -> &str {
+        OAUTH_SIGNATURE_METHOD_HMAC_VALUE
+    }
+
+    fn sign(&self, consumer_secret: &str, token_secret: &str, base_string: &str) -> Result<String> {
+        Ok(sign_string_hmac(
+            make_signing_key(consumer_secret, token_secret),
+            base_string,
+        ))
+    }
+
+    fn body_hash(&self, body: &[u8]) -> Option<String> {
+        Some(hash_body::<Sha1>(body))
+    }
+}
+
+/// HMAC-SHA256, keyed like HMAC-SHA1. Not in the spec, but required by some providers (e.g.,
+/// NetSuite).
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HmacSha256;
+
+impl SignatureMethod for HmacSha256 {
+    fn name(&self) -> &str {
+        OAUTH_SIGNATURE_METHOD_HMAC_SHA256_VALUE
+    }
+
+    fn sign(&self, consumer_secret: &str, token_secret: &str, base_string: &str) -> Result<String> {
+        Ok(sign_string_hmac_sha256(
+            make_signing_key(consumer_secret, token_secret),
+            base_string,
+        ))
+    }
+
+    fn body_hash(&self, body: &[u8]) -> Option<String> {
+        Some(hash_body::<Sha256>(body))
+    }
+}
+
+/// HMAC-SHA512, keyed like HMAC-SHA1. Not in the spec.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HmacSha512;
+
+impl SignatureMethod for HmacSha512 {
+    fn name(&self) -> &str {
+        OAUTH_SIGNATURE_METHOD_HMAC_SHA512_VALUE
+    }
+
+    fn sign(&self, consumer_secret: &str, token_secret: &str, base_string: &str) -> Result<String> {
+        Ok(sign_string_hmac_sha512(
+            make_signing_key(consumer_secret, token_secret),
+            base_string,
+        ))
+    }
+
+    fn body_hash(&self, body: &[u8]) -> Option<String> {
+        Some(hash_body::<Sha512>(body))
+    }
+}
+
+/// RSA-SHA1, using the consumer's private key. The consumer secret is unused. (Spec 9.3)
+#[derive(Clone)]
+pub struct RsaSha1 {
+    key: RsaPrivateKey,
+}
+
+impl RsaSha1 {
+    pub fn new(key: RsaPrivateKey) -> RsaSha1 {
+        RsaSha1 { key }
+    }
+
+    /// Creates an RSA-SHA1 signature method from a PEM-encoded private key.
+    /// Both PKCS#1 (`BEGIN RSA PRIVATE KEY`) and PKCS#8 (`BEGIN PRIVATE KEY`) are accepted.
+    pub fn from_pem(pem: impl AsRef<str>) -> Result<RsaSha1> {
+        let pem = pem.as_ref();
+        let key = RsaPrivateKey::from_pkcs8_pem(pem)
+            .or_else(|_| RsaPrivateKey::from_pkcs1_pem(pem))
+            .map_err(|err| OagainError::BadRsaKey(err.to_string()))?;
+        Ok(RsaSha1::new(key))
+    }
+}
+
+impl Debug for RsaSha1 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // Don't let the private key leak into the logs.
+        write!(f, "RsaSha1(..)")
+    }
+}
+
+impl SignatureMethod for RsaSha1 {
+    fn name(&self) -> &str {
+        OAUTH_SIGNATURE_METHOD_RSA_VALUE
+    }
+
+    fn sign(&self, _: &str, _: &str, base_string: &str) -> Result<String> {
+        sign_string_rsa(&self.key, base_string)
+    }
+
+    fn verify(&self, signature: &str, _: &str, _: &str, base_string: &str) -> Result<bool> {
+        let Ok(signature_bytes) = base64::engine::general_purpose::STANDARD.decode(signature)
+        else {
+            return Ok(false);
+        };
+        Ok(self
+            .key
+            .to_public_key()
+            .verify(
+                Pkcs1v15Sign::new::<Sha1>(),
+                &Sha1::digest(base_string.as_bytes()),
+                &signature_bytes,
+            )
+            .is_ok())
+    }
+
+    fn uses_consumer_secret(&self) -> bool {
+        false
+    }
+
+    fn body_hash(&self, body: &[u8]) -> Option<String> {
+        Some(hash_body::<Sha1>(body))
+    }
+}
+
+/// PLAINTEXT, which sends the signing key itself as the signature. (Spec 9.4)
+/// This offers no protection unless the request is sent over HTTPS.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PlainText;
+
+impl SignatureMethod for PlainText {
+    fn name(&self) -> &str {
+        OAUTH_SIGNATURE_METHOD_PLAINTEXT_VALUE
+    }
+
+    fn sign(&self, consumer_secret: &str, token_secret: &str, _: &str) -> Result<String> {
+        Ok(make_signing_key(consumer_secret, token_secret))
+    }
+
+    fn uses_base_string(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{HmacSha1, PlainText, SignatureMethod};
+    use crate::error::Result;
+
+    // A made-up method, to show that users can supply their own.
+    #[derive(Debug)]
+    struct Reversed;
+
+    impl SignatureMethod for Reversed {
+        fn name(&self) -> &str {
+            "X-REVERSED"
+        }
+
+        fn sign(&self, _: &str, _: &str, base_string: &str) -> Result<String> {
+            Ok(base_string.chars().rev().collect())
+        }
+    }
+
+    #[test]
+    fn custom_method() {
+        let method: Box<dyn SignatureMethod> = Box::new(Reversed);
+        assert_eq!("X-REVERSED", method.name());
+        assert_eq!("cba", method.sign("cs", "ts", "abc").unwrap());
+        assert!(method.verify("cba", "cs", "ts", "abc").unwrap());
+        assert!(!method.verify("abc", "cs", "ts", "abc").unwrap());
+    }
+
+    #[test]
+    fn verify_hmac() {
+        assert!(HmacSha1
+            .verify("egQqG5AJep5sJ7anhXju1unge2I=", "cs", "", "bs")
+            .unwrap());
+        assert!(!HmacSha1
+            .verify("egQqG5AJep5sJ7anhXju1unge2I=", "cs", "ts", "bs")
+            .unwrap());
+    }
+
+    #[test]
+    fn verify_plaintext() {
+        assert!(PlainText.verify("cs&ts", "cs", "ts", "").unwrap());
+        assert!(!PlainText.verify("cs&", "cs", "ts", "").unwrap());
+    }
+}
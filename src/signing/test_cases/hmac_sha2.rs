@@ -0,0 +1,78 @@
+use crate::consumer::Consumer;
+use crate::nonce_provider::{BasicNonce, TestEpochProvider};
+use crate::parameters::ParamPair;
+use crate::signing::{
+    sign_string_hmac_sha256, sign_string_hmac_sha512, HmacSha256, HmacSha512, SignatureMethod,
+};
+use crate::test_constants::{ACCESS_TOKEN_URL, REQUEST_TOKEN_URL, USER_AUTHORIZATION_URL};
+
+// The HMAC-SHA2 methods aren't in the OAuth spec, so there are no OAuth test cases for them.
+// Instead, the HMAC primitives are checked against the RFC 4231 test vectors (base64-encoded,
+// as they would appear in `oauth_signature`), and the methods against the wiki's HMAC-SHA1
+// request with the signature method swapped out.
+
+// RFC 4231, Test Case 2
+const RFC_4231_KEY: &str = "Jefe";
+const RFC_4231_DATA: &str = "what do ya want for nothing?";
+
+#[test]
+fn hmac_sha256_rfc_4231() {
+    // 5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843
+    assert_eq!(
+        "W9zBRr9gdU5qBCQmCJV1x1oAPwidJzmDnexYuWTsOEM=",
+        sign_string_hmac_sha256(RFC_4231_KEY, RFC_4231_DATA)
+    );
+}
+
+#[test]
+fn hmac_sha512_rfc_4231() {
+    // 164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea250554
+    // 9758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737
+    assert_eq!(
+        "Fkt6e/z4GeLjlfvnO1bgo4e9ZCIugx/WECcM1+olBVSXWL91wFqZSm0DT2X48Ob9yuqxo01Ka0tjbgcKOLznNw==",
+        sign_string_hmac_sha512(RFC_4231_KEY, RFC_4231_DATA)
+    );
+}
+
+#[test]
+fn hmac_sha256_method() {
+    let base_string = "GET&http%3A%2F%2Fphotos.example.net%2Fphotos&file%3Dvacation.jpg%26oauth_consumer_key%3Ddpf43f3p2l4k3l03%26oauth_nonce%3Dkllo9940pd9333jh%26oauth_signature_method%3DHMAC-SHA256%26oauth_timestamp%3D1191242096%26oauth_token%3Dnnch734d00sl2jdk%26oauth_version%3D1.0%26size%3Doriginal";
+    assert_eq!("HMAC-SHA256", HmacSha256.name());
+    assert_eq!(
+        "WVPzl1j6ZsnkIjWr7e3OZ3jkenL57KwaLFhYsroX1hg=",
+        HmacSha256
+            .sign("kd94hf93k423kf44", "pfkkdhi9sl3r4s00", base_string)
+            .unwrap()
+    );
+}
+
+#[test]
+fn hmac_sha512_method() {
+    let base_string = "GET&http%3A%2F%2Fphotos.example.net%2Fphotos&file%3Dvacation.jpg%26oauth_consumer_key%3Ddpf43f3p2l4k3l03%26oauth_nonce%3Dkllo9940pd9333jh%26oauth_signature_method%3DHMAC-SHA512%26oauth_timestamp%3D1191242096%26oauth_token%3Dnnch734d00sl2jdk%26oauth_version%3D1.0%26size%3Doriginal";
+    assert_eq!("HMAC-SHA512", HmacSha512.name());
+    assert_eq!(
+        "nQYVqZl8EkEH4fThSn+25i1gc68aX+FHTHSAXrxIl2ixdAofXM/pq2x90UaOFIZQxvkzE5VRZpPbjo6i+fe6rg==",
+        HmacSha512
+            .sign("kd94hf93k423kf44", "pfkkdhi9sl3r4s00", base_string)
+            .unwrap()
+    );
+}
+
+#[test]
+fn signature_method_param_follows_builder() {
+    let consumer = Consumer::<BasicNonce<TestEpochProvider>>::builder()
+        .set_request_token_url(REQUEST_TOKEN_URL)
+        .unwrap()
+        .set_user_authorization_url(USER_AUTHORIZATION_URL)
+        .unwrap()
+        .set_access_token_url(ACCESS_TOKEN_URL)
+        .unwrap()
+        .set_consumer_key("dpf43f3p2l4k3l03")
+        .set_consumer_secret("kd94hf93k423kf44")
+        .use_hmac_sha256()
+        .build_with_nonce_provider(BasicNonce::new(TestEpochProvider::new(1191242096)))
+        .unwrap();
+
+    let pairs = consumer.oauth_param_list(1191242096, "kllo9940pd9333jh");
+    assert!(pairs.contains(&ParamPair::pair("oauth_signature_method", "HMAC-SHA256")));
+}
@@ -1,2 +1,4 @@
+mod hmac_sha2;
 mod old_keys;
+mod rfc5849;
 mod wiki;
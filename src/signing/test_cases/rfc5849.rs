@@ -0,0 +1,121 @@
+use url::Url;
+
+use crate::consumer::{Consumer, ConsumerTestFuncs};
+use crate::nonce_provider::{BasicNonce, TestEpochProvider};
+use crate::parameters::ParamPair;
+use crate::signing::{concat_request_elements, HmacSha1, SignatureMethod};
+use crate::test_constants::{ACCESS_TOKEN_URL, REQUEST_TOKEN_URL, USER_AUTHORIZATION_URL};
+use crate::test_server::header_params;
+
+// RFC 5849 is the later, IETF version of the OAuth 1.0 spec. Section 3.4.1 has a worked
+// example of a signature base string for a request with a form-encoded body.
+
+fn rfc_3_4_1_form_params() -> Vec<ParamPair> {
+    // From the body "c2&a3=2+q"
+    vec![ParamPair::pair("c2", ""), ParamPair::pair("a3", "2 q")]
+}
+
+#[test]
+fn form_body_sec_3_4_1_1() {
+    let url = Url::parse("http://example.com/request?b5=%3D%253D&a3=a&c%40=&a2=r%20b").unwrap();
+    let oauth_params = [
+        ParamPair::pair("oauth_consumer_key", "9djdj82h48djs9d2"),
+        ParamPair::pair("oauth_token", "kkk9d7dh3k39sjv7"),
+        ParamPair::pair("oauth_signature_method", "HMAC-SHA1"),
+        ParamPair::pair("oauth_timestamp", "137131201"),
+        ParamPair::pair("oauth_nonce", "7d8f3e4a"),
+    ];
+    let query_params = url
+        .query_pairs()
+        .map(|(name, value)| ParamPair::pair(name, value));
+
+    let base_string = concat_request_elements(
+        "POST",
+        &url,
+        oauth_params
+            .into_iter()
+            .chain(query_params)
+            .chain(rfc_3_4_1_form_params()),
+    );
+    assert_eq!(
+        "POST&http%3A%2F%2Fexample.com%2Frequest&a2%3Dr%2520b%26a3%3D2%2520q%26a3%3Da%26b5%3D%253D%25253D%26c%2540%3D%26c2%3D%26oauth_consumer_key%3D9djdj82h48djs9d2%26oauth_nonce%3D7d8f3e4a%26oauth_signature_method%3DHMAC-SHA1%26oauth_timestamp%3D137131201%26oauth_token%3Dkkk9d7dh3k39sjv7",
+        base_string
+    );
+}
+
+#[test]
+fn form_params_are_signed() {
+    let consumer = Consumer::<BasicNonce<TestEpochProvider>>::builder()
+        .set_request_token_url(REQUEST_TOKEN_URL)
+        .unwrap()
+        .set_user_authorization_url(USER_AUTHORIZATION_URL)
+        .unwrap()
+        .set_access_token_url(ACCESS_TOKEN_URL)
+        .unwrap()
+        .set_consumer_key("9djdj82h48djs9d2")
+        .set_consumer_secret("j49sk3j29djd")
+        .build_with_nonce_provider(BasicNonce::new(TestEpochProvider::new(137131201)))
+        .unwrap();
+
+    let url = Url::parse("http://example.com/request?b5=%3D%253D&a3=a&c%40=&a2=r%20b").unwrap();
+    let header = consumer
+        .sign_request("POST", &url, &rfc_3_4_1_form_params())
+        .unwrap();
+
+    let mut params = header_params(&header);
+    let signature = params.pop().unwrap();
+    assert_eq!("oauth_signature", signature.name);
+    let signature = signature.value.unwrap();
+
+    let query_params = url
+        .query_pairs()
+        .map(|(name, value)| ParamPair::pair(name, value))
+        .collect::<Vec<_>>();
+
+    // The signature only matches if the form params were included in the base string.
+    let with_form = concat_request_elements(
+        "POST",
+        &url,
+        params
+            .iter()
+            .chain(query_params.iter())
+            .chain(rfc_3_4_1_form_params().iter())
+            .cloned(),
+    );
+    assert!(HmacSha1
+        .verify(&signature, "j49sk3j29djd", "", &with_form)
+        .unwrap());
+
+    let without_form = concat_request_elements(
+        "POST",
+        &url,
+        params.iter().chain(query_params.iter()).cloned(),
+    );
+    assert!(!HmacSha1
+        .verify(&signature, "j49sk3j29djd", "", &without_form)
+        .unwrap());
+}
+
+#[test]
+fn form_params_may_not_be_oauth() {
+    let consumer = Consumer::<BasicNonce<TestEpochProvider>>::builder()
+        .set_request_token_url(REQUEST_TOKEN_URL)
+        .unwrap()
+        .set_user_authorization_url(USER_AUTHORIZATION_URL)
+        .unwrap()
+        .set_access_token_url(ACCESS_TOKEN_URL)
+        .unwrap()
+        .set_consumer_key("9djdj82h48djs9d2")
+        .set_consumer_secret("j49sk3j29djd")
+        .build_with_nonce_provider(BasicNonce::new(TestEpochProvider::new(137131201)))
+        .unwrap();
+
+    let result = consumer.post_form(
+        &Url::parse("http://example.com/request").unwrap(),
+        [("a3", "2 q"), ("oauth_token", "sneaky")],
+    );
+    assert!(matches!(
+        result,
+        Err(crate::error::OagainError::DisallowedOauthParam(name)) if name == "oauth_token"
+    ));
+}
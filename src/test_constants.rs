@@ -1,5 +1,5 @@
-/// This is a bunch of constants to use for testing.
-/// They are harvested from the Spec Appendix A
+//! This is a bunch of constants to use for testing.
+//! They are harvested from the Spec Appendix A
 
 // Appendix A.1
 pub const REQUEST_TOKEN_URL: &str = "https://photos.example.net/request_token";
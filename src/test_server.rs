@@ -0,0 +1,166 @@
+//! A tiny HTTP server for tests. It answers a fixed number of requests with canned responses,
+//! and records the requests so that tests can inspect what was actually sent.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::thread::JoinHandle;
+
+use url::Url;
+
+use crate::parameters::ParamPair;
+use crate::pencoding::decode_str;
+
+#[derive(Debug, Clone)]
+pub struct CannedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl CannedResponse {
+    pub fn ok(body: impl Into<String>) -> CannedResponse {
+        CannedResponse::new(200, body)
+    }
+
+    pub fn new(status: u16, body: impl Into<String>) -> CannedResponse {
+        CannedResponse {
+            status,
+            headers: vec![],
+            body: body.into(),
+        }
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> CannedResponse {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub url: Url,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl RecordedRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// The params from the `Authorization: OAuth ...` header.
+    pub fn oauth_params(&self) -> Vec<ParamPair> {
+        header_params(self.header("authorization").unwrap())
+    }
+}
+
+pub struct TestServer {
+    base_url: Url,
+    handle: JoinHandle<Vec<RecordedRequest>>,
+}
+
+impl TestServer {
+    /// Starts a server that answers one request for each response, in order.
+    pub fn start(responses: Vec<CannedResponse>) -> TestServer {
+        let count = responses.len();
+        let mut responses = responses.into_iter();
+        TestServer::serve(count, move |_| responses.next().unwrap())
+    }
+
+    /// Starts a server that answers `count` requests with whatever `respond` picks for each, for
+    /// tests where the order of the requests isn't known.
+    pub fn serve(
+        count: usize,
+        mut respond: impl FnMut(&RecordedRequest) -> CannedResponse + Send + 'static,
+    ) -> TestServer {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let base_url = Url::parse(&format!("http://127.0.0.1:{}/", port)).unwrap();
+        let server_url = base_url.clone();
+        let handle = std::thread::spawn(move || {
+            (0..count)
+                .map(|_| {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let request = read_request(&server_url, &mut BufReader::new(&mut stream));
+                    write_response(&mut stream, &request, &respond(&request));
+                    request
+                })
+                .collect()
+        });
+        TestServer { base_url, handle }
+    }
+
+    pub fn url(&self, path: &str) -> Url {
+        self.base_url.join(path).unwrap()
+    }
+
+    /// Waits for all the responses to be sent, and returns the requests that were received.
+    pub fn requests(self) -> Vec<RecordedRequest> {
+        self.handle.join().unwrap()
+    }
+}
+
+fn read_request(base_url: &Url, reader: &mut impl BufRead) -> RecordedRequest {
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).unwrap();
+    let mut pieces = request_line.split_whitespace();
+    let method = pieces.next().unwrap().to_string();
+    let url = base_url.join(pieces.next().unwrap()).unwrap();
+
+    let mut headers = vec![];
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = line.split_once(':').unwrap();
+        headers.push((name.trim().to_string(), value.trim().to_string()));
+    }
+
+    let mut request = RecordedRequest {
+        method,
+        url,
+        headers,
+        body: vec![],
+    };
+    let length = request
+        .header("content-length")
+        .map(|l| l.parse::<usize>().unwrap())
+        .unwrap_or_default();
+    request.body = vec![0; length];
+    reader.read_exact(&mut request.body).unwrap();
+    request
+}
+
+fn write_response(stream: &mut impl Write, request: &RecordedRequest, response: &CannedResponse) {
+    let mut head = format!("HTTP/1.1 {} Canned\r\n", response.status);
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str(&format!(
+        "Content-Length: {}\r\nConnection: close\r\n\r\n",
+        response.body.len()
+    ));
+    stream.write_all(head.as_bytes()).unwrap();
+    if request.method != "HEAD" {
+        stream.write_all(response.body.as_bytes()).unwrap();
+    }
+}
+
+/// Splits an `Authorization: OAuth ...` header back into its params.
+pub fn header_params(header: &str) -> Vec<ParamPair> {
+    header
+        .trim_start_matches("OAuth ")
+        .split(", ")
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap();
+            ParamPair::pair(decode_str(name), decode_str(value.trim_matches('"')))
+        })
+        .collect()
+}
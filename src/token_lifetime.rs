@@ -0,0 +1,270 @@
+//! Policies for deciding whether a saved access token can still be used.
+
+use std::fmt::Debug;
+
+use chrono::{DateTime, Days, Duration, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// Whether an access token can be used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TokenStatus {
+    /// The token can be used as-is.
+    Fresh,
+    /// The token has gone inactive, but can be reactivated with `Consumer::renew_access_token()`.
+    NeedsRenewal,
+    /// The token is useless. The user will need to authorize again.
+    Expired,
+}
+
+/// Decides when an access token expires.
+///
+/// The built-in policies are [FixedAge], [IdleTimeout], [DailyCutoff], and [NeverExpires].
+/// A tuple of two policies applies both, and reports the worse status.
+pub trait TokenLifetimePolicy: Debug + Send + Sync {
+    /// The status of a token that was issued at `issued`, and last used to sign a request at
+    /// `last_used`.
+    fn status(
+        &self,
+        issued: DateTime<Utc>,
+        last_used: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> TokenStatus;
+}
+
+/// Tokens expire a fixed time after they are issued.
+#[derive(Debug, Clone, Copy)]
+pub struct FixedAge(pub Duration);
+
+impl TokenLifetimePolicy for FixedAge {
+    fn status(&self, issued: DateTime<Utc>, _: DateTime<Utc>, now: DateTime<Utc>) -> TokenStatus {
+        if now - issued >= self.0 {
+            TokenStatus::Expired
+        } else {
+            TokenStatus::Fresh
+        }
+    }
+}
+
+/// Tokens go inactive, and need renewal, when they haven't been used for a while.
+///
+/// The consumer saves the last-use time at most once a minute, so a token loaded from the store
+/// (e.g., by another process, or on the next run) can go inactive up to a minute early.
+#[derive(Debug, Clone, Copy)]
+pub struct IdleTimeout(pub Duration);
+
+impl TokenLifetimePolicy for IdleTimeout {
+    fn status(
+        &self,
+        _: DateTime<Utc>,
+        last_used: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> TokenStatus {
+        if now - last_used > self.0 {
+            TokenStatus::NeedsRenewal
+        } else {
+            TokenStatus::Fresh
+        }
+    }
+}
+
+/// Tokens expire at a fixed time of day in a named timezone (e.g., eTrade's midnight US Eastern).
+#[derive(Debug, Clone, Copy)]
+pub struct DailyCutoff {
+    timezone: Tz,
+    time: NaiveTime,
+}
+
+impl DailyCutoff {
+    pub fn new(timezone: Tz, time: NaiveTime) -> DailyCutoff {
+        DailyCutoff { timezone, time }
+    }
+
+    pub fn midnight(timezone: Tz) -> DailyCutoff {
+        DailyCutoff::new(timezone, NaiveTime::MIN)
+    }
+
+    // The most recent cutoff at or before `now`.
+    fn last_cutoff(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let local_now = now.with_timezone(&self.timezone);
+        let mut date = local_now.date_naive();
+        loop {
+            let naive = date.and_time(self.time);
+            // If the cutoff falls in a DST gap, use the first valid time after it.
+            let cutoff = self
+                .timezone
+                .from_local_datetime(&naive)
+                .earliest()
+                .or_else(|| {
+                    self.timezone
+                        .from_local_datetime(&(naive + Duration::hours(1)))
+                        .earliest()
+                });
+            match cutoff {
+                Some(cutoff) if cutoff <= local_now => return cutoff.with_timezone(&Utc),
+                // unwrap: there is always a previous day.
+                _ => date = date.checked_sub_days(Days::new(1)).unwrap(),
+            }
+        }
+    }
+}
+
+impl TokenLifetimePolicy for DailyCutoff {
+    fn status(&self, issued: DateTime<Utc>, _: DateTime<Utc>, now: DateTime<Utc>) -> TokenStatus {
+        if issued < self.last_cutoff(now) {
+            TokenStatus::Expired
+        } else {
+            TokenStatus::Fresh
+        }
+    }
+}
+
+/// Tokens never expire, which is the norm for OAuth 1.0 providers.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NeverExpires;
+
+impl TokenLifetimePolicy for NeverExpires {
+    fn status(&self, _: DateTime<Utc>, _: DateTime<Utc>, _: DateTime<Utc>) -> TokenStatus {
+        TokenStatus::Fresh
+    }
+}
+
+impl<A: TokenLifetimePolicy, B: TokenLifetimePolicy> TokenLifetimePolicy for (A, B) {
+    fn status(
+        &self,
+        issued: DateTime<Utc>,
+        last_used: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> TokenStatus {
+        self.0
+            .status(issued, last_used, now)
+            .max(self.1.status(issued, last_used, now))
+    }
+}
+
+impl TokenLifetimePolicy for Box<dyn TokenLifetimePolicy> {
+    fn status(
+        &self,
+        issued: DateTime<Utc>,
+        last_used: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> TokenStatus {
+        self.as_ref().status(issued, last_used, now)
+    }
+}
+
+/// When the current access token was issued and last used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TokenTimes {
+    pub issued: DateTime<Utc>,
+    pub last_used: DateTime<Utc>,
+}
+
+impl TokenTimes {
+    pub fn new(now: DateTime<Utc>) -> TokenTimes {
+        TokenTimes {
+            issued: now,
+            last_used: now,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{DateTime, Duration, NaiveTime, Utc};
+    use chrono_tz::US::Eastern;
+
+    use crate::token_lifetime::{
+        DailyCutoff, FixedAge, IdleTimeout, NeverExpires, TokenLifetimePolicy, TokenStatus,
+    };
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn fixed_age() {
+        let policy = FixedAge(Duration::hours(2));
+        let issued = utc("2023-12-01T10:00:00Z");
+        assert_eq!(
+            TokenStatus::Fresh,
+            policy.status(issued, issued, utc("2023-12-01T11:59:59Z"))
+        );
+        assert_eq!(
+            TokenStatus::Expired,
+            policy.status(issued, issued, utc("2023-12-01T12:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn idle_timeout() {
+        let policy = IdleTimeout(Duration::hours(2));
+        let issued = utc("2023-12-01T10:00:00Z");
+        let last_used = utc("2023-12-01T13:00:00Z");
+        assert_eq!(
+            TokenStatus::Fresh,
+            policy.status(issued, last_used, utc("2023-12-01T15:00:00Z"))
+        );
+        assert_eq!(
+            TokenStatus::NeedsRenewal,
+            policy.status(issued, last_used, utc("2023-12-01T15:00:01Z"))
+        );
+    }
+
+    #[test]
+    fn daily_cutoff() {
+        let policy = DailyCutoff::midnight(Eastern);
+        // 11pm Eastern (EST is UTC-5).
+        let issued = utc("2023-12-02T04:00:00Z");
+        assert_eq!(
+            TokenStatus::Fresh,
+            policy.status(issued, issued, utc("2023-12-02T04:59:59Z"))
+        );
+        assert_eq!(
+            TokenStatus::Expired,
+            policy.status(issued, issued, utc("2023-12-02T05:00:00Z"))
+        );
+
+        // In the summer, EDT is UTC-4.
+        let issued = utc("2023-07-01T03:30:00Z");
+        assert_eq!(
+            TokenStatus::Expired,
+            policy.status(issued, issued, utc("2023-07-01T04:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn daily_cutoff_in_dst_gap() {
+        // 2:30am doesn't exist on the day the clocks spring forward, so 3:30am is used.
+        let policy = DailyCutoff::new(Eastern, NaiveTime::from_hms_opt(2, 30, 0).unwrap());
+        let issued = utc("2024-03-10T06:00:00Z"); // 1am EST
+        assert_eq!(
+            TokenStatus::Fresh,
+            policy.status(issued, issued, utc("2024-03-10T07:29:59Z"))
+        );
+        assert_eq!(
+            TokenStatus::Expired,
+            policy.status(issued, issued, utc("2024-03-10T07:30:00Z"))
+        );
+    }
+
+    #[test]
+    fn combined() {
+        let policy = (
+            IdleTimeout(Duration::hours(2)),
+            FixedAge(Duration::hours(24)),
+        );
+        let issued = utc("2023-12-01T00:00:00Z");
+        assert_eq!(
+            TokenStatus::NeedsRenewal,
+            policy.status(issued, issued, utc("2023-12-01T03:00:00Z"))
+        );
+        assert_eq!(
+            TokenStatus::Expired,
+            policy.status(issued, issued, utc("2023-12-02T00:00:00Z"))
+        );
+        assert_eq!(
+            TokenStatus::Fresh,
+            NeverExpires.status(issued, issued, utc("2030-01-01T00:00:00Z"))
+        );
+    }
+}
@@ -0,0 +1,343 @@
+use std::fmt::{Debug, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use argon2::Argon2;
+use base64::Engine;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use toml::Value;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::error::{OagainError, Result};
+use crate::token_store::{
+    create_protected_file, lock_path, write_protected_file, SavedToken, TokenStore, TokenStoreLock,
+};
+
+const FORMAT_NAME: &str = "format";
+const FORMAT_VALUE: &str = "oagain-encrypted-v1";
+const SALT_NAME: &str = "salt";
+const NONCE_NAME: &str = "nonce";
+const CIPHERTEXT_NAME: &str = "ciphertext";
+const SALT_LEN: usize = 16;
+
+/// Where the passphrase for an [EncryptedFileStore] comes from. It is only asked for once, then
+/// kept with the derived key until the store is dropped.
+pub enum Passphrase {
+    /// Read from the named environment variable.
+    Env(String),
+    /// Returned by the callback, e.g., after prompting the user.
+    Callback(Box<dyn Fn() -> Result<String> + Send + Sync>),
+}
+
+impl Passphrase {
+    pub fn env(name: impl Into<String>) -> Passphrase {
+        Passphrase::Env(name.into())
+    }
+
+    pub fn callback(f: impl Fn() -> Result<String> + Send + Sync + 'static) -> Passphrase {
+        Passphrase::Callback(Box::new(f))
+    }
+
+    fn get(&self) -> Result<String> {
+        match self {
+            Passphrase::Env(name) => {
+                std::env::var(name).map_err(|_| OagainError::MissingPassphrase(name.clone()))
+            }
+            Passphrase::Callback(f) => f(),
+        }
+    }
+}
+
+impl Debug for Passphrase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Passphrase::Env(name) => write!(f, "Passphrase::Env({:?})", name),
+            Passphrase::Callback(_) => write!(f, "Passphrase::Callback(..)"),
+        }
+    }
+}
+
+// The key derived from the passphrase, and the salt it was derived with. The passphrase is kept
+// too, since another process may re-encrypt the file with a new salt. Both are wiped on drop.
+struct DerivedKey {
+    salt: Vec<u8>,
+    key: Key,
+    passphrase: Zeroizing<String>,
+}
+
+impl Drop for DerivedKey {
+    fn drop(&mut self) {
+        self.key.as_mut_slice().zeroize();
+    }
+}
+
+/// Keeps the token in a file encrypted with XChaCha20-Poly1305, using a key derived from a
+/// passphrase with Argon2id. The key is derived once, then reused for every save.
+pub struct EncryptedFileStore {
+    path: PathBuf,
+    passphrase: Passphrase,
+    derived_key: Mutex<Option<DerivedKey>>,
+}
+
+impl EncryptedFileStore {
+    /// Uses the file at `path`, creating it with 0600 permissions if it doesn't exist.
+    pub fn new(path: impl AsRef<Path>, passphrase: Passphrase) -> Result<EncryptedFileStore> {
+        let path = path.as_ref();
+        create_protected_file(path)?;
+        Ok(EncryptedFileStore {
+            path: path.to_path_buf(),
+            passphrase,
+            derived_key: Mutex::new(None),
+        })
+    }
+
+    // Returns the key for `salt`, deriving it if the cached key used a different salt.
+    // With no salt, the cached key is used, or a new salt is generated.
+    fn key(&self, salt: Option<&[u8]>) -> Result<(Vec<u8>, Key)> {
+        // unwrap: the lock is never held across a panic.
+        let mut derived_key = self.derived_key.lock().unwrap();
+        let passphrase = match derived_key.as_ref() {
+            Some(derived) if salt.is_none_or(|salt| salt == derived.salt) => {
+                return Ok((derived.salt.clone(), derived.key));
+            }
+            Some(derived) => derived.passphrase.clone(),
+            None => Zeroizing::new(self.passphrase.get()?),
+        };
+
+        let salt = salt.map(<[u8]>::to_vec).unwrap_or_else(|| {
+            let mut salt = vec![0; SALT_LEN];
+            OsRng.fill_bytes(&mut salt);
+            salt
+        });
+        let mut key = Key::default();
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+            .map_err(|err| OagainError::EncryptionError(err.to_string()))?;
+        *derived_key = Some(DerivedKey {
+            salt: salt.clone(),
+            key,
+            passphrase,
+        });
+        Ok((salt, key))
+    }
+}
+
+impl Debug for EncryptedFileStore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // Don't let the key leak into the logs.
+        f.debug_struct("EncryptedFileStore")
+            .field("path", &self.path)
+            .field("passphrase", &self.passphrase)
+            .finish_non_exhaustive()
+    }
+}
+
+fn encode(bytes: &[u8]) -> Value {
+    Value::String(base64::engine::general_purpose::STANDARD.encode(bytes))
+}
+
+fn decode(table: &toml::Table, name: &str) -> Result<Vec<u8>> {
+    let Some(Value::String(s)) = table.get(name) else {
+        return Err(OagainError::EncryptionError(format!("{} is missing", name)));
+    };
+    base64::engine::general_purpose::STANDARD
+        .decode(s)
+        .map_err(|err| OagainError::EncryptionError(format!("{} is not base64: {}", name, err)))
+}
+
+impl TokenStore for EncryptedFileStore {
+    fn load(&self) -> Result<Option<SavedToken>> {
+        if !self.path.try_exists()? {
+            return Ok(None);
+        }
+        let s = fs::read_to_string(&self.path)?;
+        if s.trim().is_empty() {
+            return Ok(None);
+        }
+        let table = s.parse::<toml::Table>()?;
+        if table.get(FORMAT_NAME).and_then(Value::as_str) != Some(FORMAT_VALUE) {
+            return Err(OagainError::EncryptionError(
+                "the save file is not encrypted".to_string(),
+            ));
+        }
+        let salt = decode(&table, SALT_NAME)?;
+        let nonce = decode(&table, NONCE_NAME)?;
+        let ciphertext = decode(&table, CIPHERTEXT_NAME)?;
+        if nonce.len() != XNonce::default().len() {
+            return Err(OagainError::EncryptionError("bad nonce".to_string()));
+        }
+
+        let (_, key) = self.key(Some(&salt))?;
+        let plaintext = XChaCha20Poly1305::new(&key)
+            .decrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: &ciphertext,
+                    aad: FORMAT_VALUE.as_bytes(),
+                },
+            )
+            .map_err(|_| {
+                OagainError::EncryptionError(
+                    "wrong passphrase, or the save file was altered".to_string(),
+                )
+            })?;
+        Ok(Some(serde_json::from_slice(&plaintext)?))
+    }
+
+    fn save(&self, token: &SavedToken) -> Result<()> {
+        let (salt, key) = self.key(None)?;
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = XChaCha20Poly1305::new(&key)
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: &serde_json::to_vec(token)?,
+                    aad: FORMAT_VALUE.as_bytes(),
+                },
+            )
+            .map_err(|err| OagainError::EncryptionError(err.to_string()))?;
+
+        let mut table = toml::Table::new();
+        table.insert(
+            FORMAT_NAME.to_string(),
+            Value::String(FORMAT_VALUE.to_string()),
+        );
+        table.insert(SALT_NAME.to_string(), encode(&salt));
+        table.insert(NONCE_NAME.to_string(), encode(&nonce));
+        table.insert(CIPHERTEXT_NAME.to_string(), encode(&ciphertext));
+
+        write_protected_file(&self.path, table.to_string().as_bytes())
+    }
+
+    fn clear(&self) -> Result<()> {
+        write_protected_file(&self.path, b"")
+    }
+
+    fn lock(&self) -> Result<TokenStoreLock> {
+        TokenStoreLock::lock_file(lock_path(&self.path, "lock"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use tempfile::TempDir;
+
+    use crate::consumer::test_cases::saved_token;
+    use crate::error::OagainError;
+    use crate::token_store::{EncryptedFileStore, Passphrase, TokenStore};
+
+    fn store(dir: &TempDir, passphrase: &'static str) -> EncryptedFileStore {
+        EncryptedFileStore::new(
+            dir.path().join("save.toml"),
+            Passphrase::callback(move || Ok(passphrase.to_string())),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn round_trip() {
+        let dir = TempDir::new().unwrap();
+        store(&dir, "correct horse").save(&saved_token()).unwrap();
+
+        let contents = fs::read_to_string(dir.path().join("save.toml")).unwrap();
+        assert!(contents.contains("oagain-encrypted-v1"));
+        assert!(!contents.contains("pfkkdhi9sl3r4s00"));
+
+        assert_eq!(
+            Some(saved_token()),
+            store(&dir, "correct horse").load().unwrap()
+        );
+    }
+
+    #[test]
+    fn passphrase_is_asked_for_once() {
+        let dir = TempDir::new().unwrap();
+        let asked = Arc::new(AtomicUsize::new(0));
+        let counted = Arc::clone(&asked);
+        let ours = EncryptedFileStore::new(
+            dir.path().join("save.toml"),
+            Passphrase::callback(move || {
+                counted.fetch_add(1, Ordering::SeqCst);
+                Ok("correct horse".to_string())
+            }),
+        )
+        .unwrap();
+        ours.save(&saved_token()).unwrap();
+
+        // Another process rewrites the file with a new salt.
+        store(&dir, "correct horse").save(&saved_token()).unwrap();
+
+        assert_eq!(Some(saved_token()), ours.load().unwrap());
+        assert_eq!(1, asked.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn wrong_passphrase() {
+        let dir = TempDir::new().unwrap();
+        store(&dir, "correct horse").save(&saved_token()).unwrap();
+
+        assert!(matches!(
+            store(&dir, "battery staple").load(),
+            Err(OagainError::EncryptionError(_))
+        ));
+    }
+
+    #[test]
+    fn altered_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("save.toml");
+        store(&dir, "correct horse").save(&saved_token()).unwrap();
+
+        let mut table = fs::read_to_string(&path)
+            .unwrap()
+            .parse::<toml::Table>()
+            .unwrap();
+        let mut ciphertext = STANDARD
+            .decode(table["ciphertext"].as_str().unwrap())
+            .unwrap();
+        ciphertext[0] ^= 1;
+        table.insert(
+            "ciphertext".to_string(),
+            toml::Value::String(STANDARD.encode(ciphertext)),
+        );
+        fs::write(&path, table.to_string()).unwrap();
+
+        assert!(store(&dir, "correct horse").load().is_err());
+    }
+
+    #[test]
+    fn plain_save_file_is_refused() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("save.toml"),
+            "access_token = \"abc\"\ntoken_secret = \"def\"\n",
+        )
+        .unwrap();
+
+        assert!(store(&dir, "correct horse").load().is_err());
+    }
+
+    #[test]
+    fn passphrase_from_env() {
+        let dir = TempDir::new().unwrap();
+        let store = EncryptedFileStore::new(
+            dir.path().join("save.toml"),
+            Passphrase::env("OAGAIN_TEST_PASSPHRASE_NEVER_SET"),
+        )
+        .unwrap();
+
+        assert!(matches!(
+            store.save(&saved_token()),
+            Err(OagainError::MissingPassphrase(name)) if name == "OAGAIN_TEST_PASSPHRASE_NEVER_SET"
+        ));
+    }
+}
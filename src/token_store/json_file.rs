@@ -0,0 +1,74 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::Result;
+use crate::token_store::{
+    create_protected_file, lock_path, write_protected_file, SavedToken, TokenStore, TokenStoreLock,
+};
+
+/// Keeps the token in a JSON file, with the same field names as [super::TomlFileStore].
+#[derive(Debug, Clone)]
+pub struct JsonFileStore {
+    path: PathBuf,
+}
+
+impl JsonFileStore {
+    /// Uses the file at `path`, creating it with 0600 permissions if it doesn't exist.
+    pub fn new(path: impl AsRef<Path>) -> Result<JsonFileStore> {
+        let path = path.as_ref();
+        create_protected_file(path)?;
+        Ok(JsonFileStore {
+            path: path.to_path_buf(),
+        })
+    }
+}
+
+impl TokenStore for JsonFileStore {
+    fn load(&self) -> Result<Option<SavedToken>> {
+        if !self.path.try_exists()? {
+            return Ok(None);
+        }
+        let s = fs::read_to_string(&self.path)?;
+        if s.trim().is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&s)?))
+    }
+
+    fn save(&self, token: &SavedToken) -> Result<()> {
+        write_protected_file(&self.path, serde_json::to_string_pretty(token)?.as_bytes())
+    }
+
+    fn clear(&self) -> Result<()> {
+        write_protected_file(&self.path, b"")
+    }
+
+    fn lock(&self) -> Result<TokenStoreLock> {
+        TokenStoreLock::lock_file(lock_path(&self.path, "lock"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tempfile::TempDir;
+
+    use crate::consumer::test_cases::saved_token;
+    use crate::token_store::{JsonFileStore, TokenStore};
+
+    #[test]
+    fn round_trip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("save.json");
+        let store = JsonFileStore::new(&path).unwrap();
+        assert_eq!(None, store.load().unwrap());
+
+        store.save(&saved_token()).unwrap();
+        assert_eq!(Some(saved_token()), store.load().unwrap());
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!("2023-12-01T10:00:00Z", json["token_save_time"]);
+
+        store.clear().unwrap();
+        assert_eq!(None, store.load().unwrap());
+    }
+}
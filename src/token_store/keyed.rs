@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fs::{self, DirBuilder};
+use std::io::ErrorKind;
+use std::os::unix::fs::DirBuilderExt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::error::Result;
+use crate::pencoding::encode_param;
+use crate::token_store::{write_protected_file, SavedToken};
+
+/// Somewhere to keep the access tokens of many users, each under a key chosen by the application
+/// (e.g., its own user ID). Used with a shared [crate::ConsumerConfig].
+///
+/// The built-in stores are [KeyedMemoryStore] and [DirectoryStore].
+pub trait KeyedTokenStore: Debug + Send + Sync {
+    /// Returns the token saved under `key`, or None if there isn't one.
+    fn load(&self, key: &str) -> Result<Option<SavedToken>>;
+
+    /// Replaces the token saved under `key` with `token`.
+    fn save(&self, key: &str, token: &SavedToken) -> Result<()>;
+
+    /// Forgets the token saved under `key`.
+    fn clear(&self, key: &str) -> Result<()>;
+}
+
+/// Keeps the tokens in memory, so they last only as long as the process. Clones share the same
+/// tokens.
+#[derive(Debug, Default, Clone)]
+pub struct KeyedMemoryStore {
+    tokens: Arc<Mutex<HashMap<String, SavedToken>>>,
+}
+
+impl KeyedMemoryStore {
+    pub fn new() -> KeyedMemoryStore {
+        KeyedMemoryStore::default()
+    }
+}
+
+impl KeyedTokenStore for KeyedMemoryStore {
+    fn load(&self, key: &str) -> Result<Option<SavedToken>> {
+        // unwrap: the lock is never held across a panic.
+        Ok(self.tokens.lock().unwrap().get(key).cloned())
+    }
+
+    fn save(&self, key: &str, token: &SavedToken) -> Result<()> {
+        self.tokens
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), token.clone());
+        Ok(())
+    }
+
+    fn clear(&self, key: &str) -> Result<()> {
+        self.tokens.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+/// Keeps each token in its own JSON file in a directory. File names are the percent-encoded key,
+/// so any key is safe to use.
+#[derive(Debug, Clone)]
+pub struct DirectoryStore {
+    dir: PathBuf,
+}
+
+impl DirectoryStore {
+    /// Uses the directory at `dir`, creating it with 0700 permissions if it doesn't exist.
+    pub fn new(dir: impl AsRef<Path>) -> Result<DirectoryStore> {
+        let dir = dir.as_ref();
+        DirBuilder::new().recursive(true).mode(0o700).create(dir)?;
+        Ok(DirectoryStore {
+            dir: dir.to_path_buf(),
+        })
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", encode_param(key)))
+    }
+}
+
+impl KeyedTokenStore for DirectoryStore {
+    fn load(&self, key: &str) -> Result<Option<SavedToken>> {
+        match fs::read_to_string(self.path(key)) {
+            Ok(s) => Ok(Some(serde_json::from_str(&s)?)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save(&self, key: &str, token: &SavedToken) -> Result<()> {
+        write_protected_file(
+            &self.path(key),
+            serde_json::to_string_pretty(token)?.as_bytes(),
+        )
+    }
+
+    fn clear(&self, key: &str) -> Result<()> {
+        match fs::remove_file(self.path(key)) {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::os::unix::fs::PermissionsExt;
+
+    use chrono::Utc;
+    use tempfile::TempDir;
+
+    use crate::token_store::{DirectoryStore, KeyedMemoryStore, KeyedTokenStore, SavedToken};
+
+    fn token(access_token: &str) -> SavedToken {
+        let now = Utc::now();
+        SavedToken {
+            access_token: access_token.to_string(),
+            token_secret: "pfkkdhi9sl3r4s00".to_string(),
+            issued: now,
+            last_used: now,
+        }
+    }
+
+    fn keeps_tokens_apart(store: &impl KeyedTokenStore) {
+        let alice = token("alice-token");
+        let bob = token("bob-token");
+        store.save("alice", &alice).unwrap();
+        store.save("../bob", &bob).unwrap();
+
+        assert_eq!(Some(alice), store.load("alice").unwrap());
+        assert_eq!(Some(bob), store.load("../bob").unwrap());
+        assert_eq!(None, store.load("carol").unwrap());
+
+        store.clear("alice").unwrap();
+        store.clear("carol").unwrap();
+        assert_eq!(None, store.load("alice").unwrap());
+        assert!(store.load("../bob").unwrap().is_some());
+    }
+
+    #[test]
+    fn memory_store_keeps_tokens_apart() {
+        keeps_tokens_apart(&KeyedMemoryStore::new());
+    }
+
+    #[test]
+    fn directory_store_keeps_tokens_apart() {
+        let parent = TempDir::new().unwrap();
+        let dir = parent.path().join("tokens");
+        let store = DirectoryStore::new(&dir).unwrap();
+        keeps_tokens_apart(&store);
+
+        // The key can't escape the directory.
+        assert!(dir.join("..%2Fbob.json").exists());
+        assert_eq!(1, std::fs::read_dir(parent.path()).unwrap().count());
+        assert_eq!(0o700, dir.metadata().unwrap().permissions().mode() & 0o777);
+    }
+}
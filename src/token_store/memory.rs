@@ -0,0 +1,59 @@
+use std::sync::{Arc, Mutex};
+
+use crate::error::Result;
+use crate::token_store::{SavedToken, TokenStore};
+
+/// Keeps the token in memory, so it lasts only as long as the process. Clones share the same
+/// token, so a clone can be kept to inspect what the consumer saved.
+#[derive(Debug, Default, Clone)]
+pub struct MemoryStore {
+    token: Arc<Mutex<Option<SavedToken>>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> MemoryStore {
+        MemoryStore::default()
+    }
+
+    /// A store that starts out holding `token`.
+    pub fn with_token(token: SavedToken) -> MemoryStore {
+        MemoryStore {
+            token: Arc::new(Mutex::new(Some(token))),
+        }
+    }
+}
+
+impl TokenStore for MemoryStore {
+    fn load(&self) -> Result<Option<SavedToken>> {
+        // unwrap: the lock is never held across a panic.
+        Ok(self.token.lock().unwrap().clone())
+    }
+
+    fn save(&self, token: &SavedToken) -> Result<()> {
+        *self.token.lock().unwrap() = Some(token.clone());
+        Ok(())
+    }
+
+    fn clear(&self) -> Result<()> {
+        *self.token.lock().unwrap() = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::consumer::test_cases::saved_token;
+    use crate::token_store::{MemoryStore, TokenStore};
+
+    #[test]
+    fn clones_share_the_token() {
+        let token = saved_token();
+        let store = MemoryStore::new();
+        let clone = store.clone();
+
+        store.save(&token).unwrap();
+        assert_eq!(Some(token), clone.load().unwrap());
+        clone.clear().unwrap();
+        assert_eq!(None, store.load().unwrap());
+    }
+}
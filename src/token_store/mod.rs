@@ -0,0 +1,182 @@
+//! Persistence for access tokens, so that users don't have to authorize on every run.
+
+mod encrypted_file;
+mod json_file;
+mod keyed;
+mod memory;
+mod toml_file;
+
+use std::fmt::Debug;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::{MetadataExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use chrono::{DateTime, Utc};
+use fs4::fs_std::FileExt;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+
+pub use encrypted_file::{EncryptedFileStore, Passphrase};
+pub use json_file::JsonFileStore;
+pub use keyed::{DirectoryStore, KeyedMemoryStore, KeyedTokenStore};
+pub use memory::MemoryStore;
+pub use toml_file::TomlFileStore;
+
+/// An access token, as persisted by a [TokenStore].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedToken {
+    pub access_token: String,
+    pub token_secret: String,
+    /// When the token was retrieved from the server.
+    #[serde(rename = "token_save_time")]
+    pub issued: DateTime<Utc>,
+    /// When the token was last used to sign a request.
+    #[serde(rename = "token_last_use")]
+    pub last_used: DateTime<Utc>,
+}
+
+/// Somewhere to keep the access token between runs.
+///
+/// The built-in stores are [TomlFileStore], [JsonFileStore], [EncryptedFileStore], and
+/// [MemoryStore]. Other stores (e.g., a database or a secrets manager) can be supplied by
+/// implementing this trait and passing them to `Builder::use_token_store()`.
+pub trait TokenStore: Debug + Send + Sync {
+    /// Returns the saved token, or None if there isn't one.
+    fn load(&self) -> Result<Option<SavedToken>>;
+
+    /// Replaces the saved token with `token`.
+    fn save(&self, token: &SavedToken) -> Result<()>;
+
+    /// Forgets the saved token.
+    fn clear(&self) -> Result<()>;
+
+    /// Blocks until this process has exclusive use of the store, which lasts until the returned
+    /// lock is dropped. The consumer holds it while authorizing, so that processes sharing a store
+    /// don't each ask the user to authorize. The default does no locking.
+    fn lock(&self) -> Result<TokenStoreLock> {
+        Ok(TokenStoreLock::default())
+    }
+}
+
+/// Exclusive use of a [TokenStore], released when dropped.
+#[derive(Debug, Default)]
+pub struct TokenStoreLock {
+    // Closing the file releases the lock.
+    _file: Option<File>,
+}
+
+impl TokenStoreLock {
+    /// Takes an advisory lock on the file at `path`, creating it if needed.
+    pub fn lock_file(path: impl AsRef<Path>) -> Result<TokenStoreLock> {
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .mode(0o600)
+            .open(path)?;
+        file.lock_exclusive()?;
+        Ok(TokenStoreLock { _file: Some(file) })
+    }
+}
+
+// Locks are kept next to the save file, rather than on it, since saving replaces the file.
+// `name` distinguishes locks on the same file, e.g., `save.toml.lock` and `save.toml.write.lock`.
+fn lock_path(path: &Path, name: &str) -> PathBuf {
+    let mut lock_path = path.as_os_str().to_owned();
+    lock_path.push(format!(".{}", name));
+    PathBuf::from(lock_path)
+}
+
+// Replaces the file at `path` with `contents`. The contents are written to a temp file with 0600
+// permissions, which is then renamed over `path`, so a reader never sees a partial file.
+fn write_protected_file(path: &Path, contents: &[u8]) -> Result<()> {
+    static TEMP_COUNT: AtomicUsize = AtomicUsize::new(0);
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let temp_path = path.with_file_name(format!(
+        ".{}.{}-{}.tmp",
+        file_name,
+        std::process::id(),
+        TEMP_COUNT.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let result = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&temp_path)
+        .and_then(|mut f| {
+            f.write_all(contents)?;
+            f.sync_all()
+        })
+        .and_then(|_| fs::rename(&temp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    Ok(result?)
+}
+
+// Creates the file if it doesn't exist, readable only by the owner. Warns if an existing file
+// is readable by others.
+fn create_protected_file(path: &Path) -> Result<()> {
+    if path.try_exists()? {
+        if path.metadata()?.mode() & 0o777 != 0o600 {
+            warn!(
+                "File permissions on save file should be 0600: {}",
+                path.to_string_lossy()
+            );
+        }
+    } else {
+        write_protected_file(path, b"")?;
+        info!("save file created at '{}'", path.to_string_lossy());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    use tempfile::TempDir;
+
+    use crate::token_store::{lock_path, write_protected_file, TokenStoreLock};
+
+    #[test]
+    fn writes_are_protected() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("save.toml");
+        fs::write(&path, "old").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_protected_file(&path, b"new").unwrap();
+
+        assert_eq!("new", fs::read_to_string(&path).unwrap());
+        assert_eq!(0o600, path.metadata().unwrap().mode() & 0o777);
+        // No temp files are left behind.
+        assert_eq!(1, fs::read_dir(dir.path()).unwrap().count());
+    }
+
+    #[test]
+    fn lock_is_exclusive() {
+        let dir = TempDir::new().unwrap();
+        let path = lock_path(&dir.path().join("save.toml"), "lock");
+        let lock = TokenStoreLock::lock_file(&path).unwrap();
+
+        let (sender, receiver) = mpsc::channel();
+        let waiter = std::thread::spawn(move || {
+            let _lock = TokenStoreLock::lock_file(&path).unwrap();
+            sender.send(()).unwrap();
+        });
+        assert!(receiver.recv_timeout(Duration::from_millis(200)).is_err());
+
+        drop(lock);
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        waiter.join().unwrap();
+    }
+}
@@ -0,0 +1,264 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use toml::Value;
+
+use crate::constants::{
+    ACCESS_TOKEN_NAME, PROFILE_TABLE_NAME, TOKEN_LAST_USE, TOKEN_SAVE_TIME, TOKEN_SECRET_NAME,
+};
+use crate::error::{OagainError, Result};
+use crate::pencoding::encode_param;
+use crate::profile::{profile_table, profile_table_mut};
+use crate::token_store::{
+    create_protected_file, lock_path, write_protected_file, SavedToken, TokenStore, TokenStoreLock,
+};
+
+/// Keeps the token in a TOML file. This is the format written by `Builder::use_save_file()`.
+///
+/// With a profile, the token is kept in the `[profile.name]` table, and the other tables in the
+/// file are left alone.
+#[derive(Debug, Clone)]
+pub struct TomlFileStore {
+    path: PathBuf,
+    profile: Option<String>,
+}
+
+impl TomlFileStore {
+    /// Uses the file at `path`, creating it with 0600 permissions if it doesn't exist.
+    pub fn new(path: impl AsRef<Path>) -> Result<TomlFileStore> {
+        let path = path.as_ref();
+        create_protected_file(path)?;
+        Ok(TomlFileStore {
+            path: path.to_path_buf(),
+            profile: None,
+        })
+    }
+
+    /// Keeps the token in the `[profile.name]` table.
+    pub fn with_profile(mut self, profile: impl Into<String>) -> TomlFileStore {
+        self.profile = Some(profile.into());
+        self
+    }
+
+    fn read_table(&self) -> Result<toml::Table> {
+        if !self.path.try_exists()? {
+            return Ok(toml::Table::new());
+        }
+        Ok(fs::read_to_string(&self.path)?.parse::<toml::Table>()?)
+    }
+
+    // Replaces this store's table with `token_table`, or removes it if None. Other profiles share
+    // the file, so they are kept, and a lock is held while reading and rewriting it.
+    fn write_table(&self, token_table: Option<toml::Table>) -> Result<()> {
+        let _lock = TokenStoreLock::lock_file(lock_path(&self.path, "write.lock"))?;
+        let mut table = self.read_table()?;
+        match (&self.profile, token_table) {
+            (Some(profile), Some(token_table)) => {
+                *profile_table_mut(&mut table, profile) = token_table
+            }
+            (Some(profile), None) => {
+                if let Some(Value::Table(profiles)) = table.get_mut(PROFILE_TABLE_NAME) {
+                    profiles.remove(profile);
+                }
+            }
+            (None, token_table) => {
+                let profiles = table.remove(PROFILE_TABLE_NAME);
+                table = token_table.unwrap_or_default();
+                if let Some(profiles) = profiles {
+                    table.insert(PROFILE_TABLE_NAME.to_string(), profiles);
+                }
+            }
+        }
+        write_protected_file(&self.path, table.to_string().as_bytes())
+    }
+}
+
+fn toml_datetime(utc: DateTime<Utc>) -> toml::value::Datetime {
+    toml::value::Datetime {
+        date: Some(toml::value::Date {
+            year: utc.year() as u16,
+            month: utc.month() as u8,
+            day: utc.day() as u8,
+        }),
+        time: Some(toml::value::Time {
+            hour: utc.hour() as u8,
+            minute: utc.minute() as u8,
+            second: utc.second() as u8,
+            nanosecond: 0,
+        }),
+        offset: Some(toml::value::Offset::Z),
+    }
+}
+
+fn chrono_datetime(datetime: &toml::value::Datetime) -> Result<DateTime<Utc>> {
+    Ok(DateTime::parse_from_rfc3339(&datetime.to_string())?.with_timezone(&Utc))
+}
+
+impl TokenStore for TomlFileStore {
+    fn load(&self) -> Result<Option<SavedToken>> {
+        let table = self.read_table()?;
+        let Some(table) = profile_table(&table, self.profile.as_deref()) else {
+            return Ok(None);
+        };
+        // At the top level, a file holding only other profiles' tokens has no token.
+        let only_profiles =
+            self.profile.is_none() && table.len() == 1 && table.contains_key(PROFILE_TABLE_NAME);
+        if table.is_empty() || only_profiles {
+            return Ok(None);
+        }
+
+        let Some(Value::String(key)) = table.get(ACCESS_TOKEN_NAME) else {
+            return Err(OagainError::MissingAccessToken);
+        };
+        let Some(Value::String(secret)) = table.get(TOKEN_SECRET_NAME) else {
+            return Err(OagainError::MissingTokenSecret);
+        };
+        let Some(Value::Datetime(save_time)) = table.get(TOKEN_SAVE_TIME) else {
+            return Err(OagainError::MissingTokenSaveTime);
+        };
+        let issued = chrono_datetime(save_time)?;
+        // Older save files only have the save time.
+        let last_used = match table.get(TOKEN_LAST_USE) {
+            Some(Value::Datetime(last_use)) => chrono_datetime(last_use)?,
+            _ => issued,
+        };
+
+        Ok(Some(SavedToken {
+            access_token: key.to_string(),
+            token_secret: secret.to_string(),
+            issued,
+            last_used,
+        }))
+    }
+
+    fn save(&self, token: &SavedToken) -> Result<()> {
+        let mut table = toml::Table::new();
+        table.insert(
+            ACCESS_TOKEN_NAME.to_string(),
+            Value::String(token.access_token.clone()),
+        );
+        table.insert(
+            TOKEN_SECRET_NAME.to_string(),
+            Value::String(token.token_secret.clone()),
+        );
+        table.insert(
+            TOKEN_SAVE_TIME.to_string(),
+            Value::Datetime(toml_datetime(token.issued)),
+        );
+        table.insert(
+            TOKEN_LAST_USE.to_string(),
+            Value::Datetime(toml_datetime(token.last_used)),
+        );
+
+        self.write_table(Some(table))
+    }
+
+    fn clear(&self) -> Result<()> {
+        self.write_table(None)
+    }
+
+    // Each profile has its own lock, so that profiles can be authorized at the same time. The
+    // name is encoded and prefixed, so that it stays in the directory and can't be another lock.
+    fn lock(&self) -> Result<TokenStoreLock> {
+        let name = match &self.profile {
+            Some(profile) => format!("profile-{}.lock", encode_param(profile)),
+            None => "lock".to_string(),
+        };
+        TokenStoreLock::lock_file(lock_path(&self.path, &name))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::os::unix::fs::MetadataExt;
+
+    use tempfile::TempDir;
+
+    use crate::consumer::test_cases::saved_token;
+    use crate::token_store::{SavedToken, TokenStore, TomlFileStore};
+
+    #[test]
+    fn round_trip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("save.toml");
+        let store = TomlFileStore::new(&path).unwrap();
+        assert_eq!(0o600, path.metadata().unwrap().mode() & 0o777);
+        assert_eq!(None, store.load().unwrap());
+
+        let token = saved_token();
+        store.save(&token).unwrap();
+        assert_eq!(Some(token), store.load().unwrap());
+
+        store.clear().unwrap();
+        assert_eq!(None, store.load().unwrap());
+        assert!(fs::read_to_string(&path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn old_format() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("save.toml");
+        fs::write(
+            &path,
+            "access_token = \"abc\"\ntoken_secret = \"def\"\ntoken_save_time = 2023-12-01T10:00:00Z\n",
+        )
+        .unwrap();
+
+        let token = TomlFileStore::new(&path).unwrap().load().unwrap().unwrap();
+        assert_eq!("abc", token.access_token);
+        assert_eq!(token.issued, token.last_used);
+    }
+
+    #[test]
+    fn profiles() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("save.toml");
+        let sandbox = TomlFileStore::new(&path).unwrap().with_profile("sandbox");
+        let production = TomlFileStore::new(&path)
+            .unwrap()
+            .with_profile("production");
+        let token = |access_token: &str| SavedToken {
+            access_token: access_token.to_string(),
+            ..saved_token()
+        };
+
+        sandbox.save(&token("sandbox-token")).unwrap();
+        production.save(&token("production-token")).unwrap();
+        assert_eq!(Some(token("sandbox-token")), sandbox.load().unwrap());
+        assert_eq!(Some(token("production-token")), production.load().unwrap());
+        let table = fs::read_to_string(&path)
+            .unwrap()
+            .parse::<toml::Table>()
+            .unwrap();
+        assert_eq!(
+            "sandbox-token",
+            table["profile"]["sandbox"]["access_token"]
+                .as_str()
+                .unwrap()
+        );
+
+        sandbox.clear().unwrap();
+        assert_eq!(None, sandbox.load().unwrap());
+        assert_eq!(Some(token("production-token")), production.load().unwrap());
+        assert_eq!(None, TomlFileStore::new(&path).unwrap().load().unwrap());
+    }
+
+    #[test]
+    fn profile_locks_are_separate() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("save.toml");
+        let token = saved_token();
+
+        // Saving takes the file's own write lock, which a profile named "write" must not hold.
+        let store = TomlFileStore::new(&path).unwrap().with_profile("write");
+        let _lock = store.lock().unwrap();
+        store.save(&token).unwrap();
+        assert!(dir.path().join("save.toml.profile-write.lock").exists());
+
+        let store = TomlFileStore::new(&path).unwrap().with_profile("../up");
+        let _lock = store.lock().unwrap();
+        assert!(dir.path().join("save.toml.profile-..%2Fup.lock").exists());
+    }
+}